@@ -1,11 +1,14 @@
 mod base;
+mod dump;
 mod expr;
 mod node;
 mod operator;
 mod stmt;
 mod traverse;
+mod unparse;
 
 pub use self::base::*;
+pub use self::dump::*;
 pub use self::expr::*;
 pub use self::node::*;
 pub use self::operator::*;
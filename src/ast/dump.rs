@@ -0,0 +1,167 @@
+use std::fmt::Write;
+
+use serde_json::{Map, Value as JsonValue};
+
+use super::expr::*;
+use super::node::*;
+use super::stmt::*;
+
+// Converts a node and everything inside it to JSON. Each node is an object with its kind, its span
+// and its children, plus any values that aren't nodes themselves such as literals and operators.
+pub fn dump_json(node: Node<'_>) -> JsonValue {
+    let span = node.info().span();
+    let mut object = Map::new();
+    object.insert("kind".into(), node.kind().into());
+    object.insert(
+        "span".into(),
+        serde_json::json!({ "start": span.start(), "end": span.end() }),
+    );
+
+    for (name, value) in attributes(&node) {
+        object.insert(name.into(), value);
+    }
+
+    object.insert(
+        "children".into(),
+        node.children().into_iter().map(dump_json).collect(),
+    );
+
+    JsonValue::Object(object)
+}
+
+// Prints a node and everything inside it as an indented tree with one node per line.
+pub fn dump_tree(node: Node<'_>) -> String {
+    let mut output = String::new();
+    write_tree(&mut output, node, 0);
+    output
+}
+
+fn write_tree(output: &mut String, node: Node<'_>, depth: usize) {
+    let span = node.info().span();
+    write!(
+        output,
+        "{}{} {}..{}",
+        "  ".repeat(depth),
+        node.kind(),
+        span.start(),
+        span.end()
+    )
+    .unwrap();
+
+    for (name, value) in attributes(&node) {
+        write!(output, " {}={}", name, value).unwrap();
+    }
+
+    output.push('\n');
+    for child in node.children() {
+        write_tree(output, child, depth + 1);
+    }
+}
+
+fn attributes(node: &Node<'_>) -> Vec<(&'static str, JsonValue)> {
+    match node {
+        Node::Ident(ident) => vec![("text", ident.text.as_str().into())],
+        Node::BooleanExpr(expr) => vec![("value", expr.value.into())],
+        Node::IntExpr(expr) => vec![("value", expr.value.into())],
+        Node::FloatExpr(expr) => vec![("value", expr.value.into())],
+        Node::StringExpr(expr) => vec![("value", expr.value.as_str().into())],
+        Node::ObjectExprPair(pair) => vec![(
+            "key",
+            match pair.key {
+                ObjectExprKeyVariant::Identifier(..) => "identifier",
+                ObjectExprKeyVariant::String(..) => "string",
+                ObjectExprKeyVariant::Expr(..) => "expr",
+            }
+            .into(),
+        )],
+        Node::FunctionExpr(expr) => match &expr.name {
+            Some(name) => vec![("name", name.text.as_str().into())],
+            None => vec![],
+        },
+        Node::UnaryOperationExpr(expr) => vec![("operator", format!("{:?}", expr.operator).into())],
+        Node::BinaryOperationExpr(expr) => {
+            vec![("operator", format!("{:?}", expr.operator).into())]
+        }
+        Node::FunctionStmt(stmt) => vec![("exported", stmt.is_exported.into())],
+        Node::VariableDeclarationStmt(stmt) => vec![("exported", stmt.is_exported.into())],
+        Node::VariableAssignmentStmt(stmt) => {
+            vec![("operator", format!("{:?}", stmt.operator).into())]
+        }
+        Node::IndexAssignmentStmt(stmt) => {
+            vec![("operator", format!("{:?}", stmt.operator).into())]
+        }
+        Node::DotAssignmentStmt(stmt) => {
+            vec![("operator", format!("{:?}", stmt.operator).into())]
+        }
+        Node::ImportStmt(stmt) => vec![(
+            "namespace",
+            matches!(stmt.variant, ImportStmtVariant::Namespace(..)).into(),
+        )],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::*;
+    use crate::parser::Parser;
+
+    fn parse(source: &str) -> Chunk {
+        Parser::new(source)
+            .parse()
+            .unwrap_or_else(|error| panic!("{}", error))
+    }
+
+    #[test]
+    fn json() {
+        let chunk = parse("let x = -1.5 + y;");
+        let json = dump_json(Node::Chunk(&chunk));
+
+        let stmt = &json["children"][0];
+        assert_eq!(stmt["kind"], "VariableDeclarationStmt");
+        assert_eq!(stmt["exported"], false);
+        assert_eq!(stmt["children"][0]["text"], "x");
+
+        let expr = &stmt["children"][1];
+        assert_eq!(expr["kind"], "BinaryOperationExpr");
+        assert_eq!(expr["operator"], "Add");
+        assert_eq!(expr["span"]["start"], 8);
+        assert_eq!(expr["span"]["end"], 16);
+        assert_eq!(expr["children"][1]["kind"], "VariableExpr");
+    }
+
+    #[test]
+    fn tree() {
+        let chunk = parse("if a { f(\"b\"); } else { return [1, true]; }");
+        let tree = dump_tree(Node::Chunk(&chunk));
+        let lines = tree.lines().map(str::trim).collect::<Vec<_>>();
+        let kinds = lines
+            .iter()
+            .map(|line| line.split(' ').next().unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                "Chunk",
+                "IfStmt",
+                "VariableExpr",
+                "Ident",
+                "Block",
+                "ExprStmt",
+                "CallExpr",
+                "VariableExpr",
+                "Ident",
+                "StringExpr",
+                "ElseClause",
+                "Block",
+                "ReturnStmt",
+                "ListExpr",
+                "IntExpr",
+                "BooleanExpr",
+            ]
+        );
+        assert!(lines.contains(&"Ident 7..8 text=\"f\""));
+        assert!(tree.contains("\n          StringExpr 9..12 value=\"b\"\n"));
+    }
+}
@@ -44,6 +44,7 @@ pub enum Node<'a> {
     VariableExpr(&'a VariableExpr),
     ListExpr(&'a ListExpr),
     ObjectExpr(&'a ObjectExpr),
+    ObjectExprPair(&'a ObjectExprPair),
     FunctionExpr(&'a FunctionExpr),
     WrappedExpr(&'a WrappedExpr),
     IndexExpr(&'a IndexExpr),
@@ -53,6 +54,7 @@ pub enum Node<'a> {
     BinaryOperationExpr(&'a BinaryOperationExpr),
     // Stmts
     IfStmt(&'a IfStmt),
+    ElseClause(&'a ElseClause),
     LoopStmt(&'a LoopStmt),
     WhileStmt(&'a WhileStmt),
     ReturnStmt(&'a ReturnStmt),
@@ -63,6 +65,9 @@ pub enum Node<'a> {
     VariableAssignmentStmt(&'a VariableAssignmentStmt),
     IndexAssignmentStmt(&'a IndexAssignmentStmt),
     DotAssignmentStmt(&'a DotAssignmentStmt),
+    ImportStmt(&'a ImportStmt),
+    ImportSpecifier(&'a ImportSpecifier),
+    ExportFromStmt(&'a ExportFromStmt),
     ExprStmt(&'a ExprStmt),
 }
 
@@ -100,7 +105,215 @@ impl<'a> Node<'a> {
             Stmt::VariableAssignment(stmt) => Self::VariableAssignmentStmt(stmt),
             Stmt::IndexAssignment(stmt) => Self::IndexAssignmentStmt(stmt),
             Stmt::DotAssignment(stmt) => Self::DotAssignmentStmt(stmt),
+            Stmt::Import(stmt) => Self::ImportStmt(stmt),
+            Stmt::ExportFrom(stmt) => Self::ExportFromStmt(stmt),
             Stmt::Expr(stmt) => Self::ExprStmt(stmt),
         }
     }
+
+    pub fn info(&self) -> &'a NodeInfo {
+        match self {
+            Self::Chunk(node) => &node.info,
+            Self::Block(node) => &node.info,
+            Self::Ident(node) => &node.info,
+            Self::NullExpr(node) => &node.info,
+            Self::BooleanExpr(node) => &node.info,
+            Self::IntExpr(node) => &node.info,
+            Self::FloatExpr(node) => &node.info,
+            Self::StringExpr(node) => &node.info,
+            Self::VariableExpr(node) => &node.info,
+            Self::ListExpr(node) => &node.info,
+            Self::ObjectExpr(node) => &node.info,
+            Self::ObjectExprPair(node) => &node.info,
+            Self::FunctionExpr(node) => &node.info,
+            Self::WrappedExpr(node) => &node.info,
+            Self::IndexExpr(node) => &node.info,
+            Self::DotExpr(node) => &node.info,
+            Self::CallExpr(node) => &node.info,
+            Self::UnaryOperationExpr(node) => &node.info,
+            Self::BinaryOperationExpr(node) => &node.info,
+            Self::IfStmt(node) => &node.info,
+            Self::ElseClause(node) => &node.info,
+            Self::LoopStmt(node) => &node.info,
+            Self::WhileStmt(node) => &node.info,
+            Self::ReturnStmt(node) => &node.info,
+            Self::BreakStmt(node) => &node.info,
+            Self::ContinueStmt(node) => &node.info,
+            Self::FunctionStmt(node) => &node.info,
+            Self::VariableDeclarationStmt(node) => &node.info,
+            Self::VariableAssignmentStmt(node) => &node.info,
+            Self::IndexAssignmentStmt(node) => &node.info,
+            Self::DotAssignmentStmt(node) => &node.info,
+            Self::ImportStmt(node) => &node.info,
+            Self::ImportSpecifier(node) => &node.info,
+            Self::ExportFromStmt(node) => &node.info,
+            Self::ExprStmt(node) => &node.info,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Chunk(..) => "Chunk",
+            Self::Block(..) => "Block",
+            Self::Ident(..) => "Ident",
+            Self::NullExpr(..) => "NullExpr",
+            Self::BooleanExpr(..) => "BooleanExpr",
+            Self::IntExpr(..) => "IntExpr",
+            Self::FloatExpr(..) => "FloatExpr",
+            Self::StringExpr(..) => "StringExpr",
+            Self::VariableExpr(..) => "VariableExpr",
+            Self::ListExpr(..) => "ListExpr",
+            Self::ObjectExpr(..) => "ObjectExpr",
+            Self::ObjectExprPair(..) => "ObjectExprPair",
+            Self::FunctionExpr(..) => "FunctionExpr",
+            Self::WrappedExpr(..) => "WrappedExpr",
+            Self::IndexExpr(..) => "IndexExpr",
+            Self::DotExpr(..) => "DotExpr",
+            Self::CallExpr(..) => "CallExpr",
+            Self::UnaryOperationExpr(..) => "UnaryOperationExpr",
+            Self::BinaryOperationExpr(..) => "BinaryOperationExpr",
+            Self::IfStmt(..) => "IfStmt",
+            Self::ElseClause(..) => "ElseClause",
+            Self::LoopStmt(..) => "LoopStmt",
+            Self::WhileStmt(..) => "WhileStmt",
+            Self::ReturnStmt(..) => "ReturnStmt",
+            Self::BreakStmt(..) => "BreakStmt",
+            Self::ContinueStmt(..) => "ContinueStmt",
+            Self::FunctionStmt(..) => "FunctionStmt",
+            Self::VariableDeclarationStmt(..) => "VariableDeclarationStmt",
+            Self::VariableAssignmentStmt(..) => "VariableAssignmentStmt",
+            Self::IndexAssignmentStmt(..) => "IndexAssignmentStmt",
+            Self::DotAssignmentStmt(..) => "DotAssignmentStmt",
+            Self::ImportStmt(..) => "ImportStmt",
+            Self::ImportSpecifier(..) => "ImportSpecifier",
+            Self::ExportFromStmt(..) => "ExportFromStmt",
+            Self::ExprStmt(..) => "ExprStmt",
+        }
+    }
+
+    // The nodes directly inside this one, in the order they appear in the source.
+    pub fn children(&self) -> Vec<Node<'a>> {
+        match *self {
+            // Base
+            Self::Chunk(Chunk { stmts, .. }) | Self::Block(Block { stmts, .. }) => {
+                stmts.iter().map(Node::from_stmt).collect()
+            }
+            Self::Ident(..) => vec![],
+            // Expressions
+            Self::NullExpr(..)
+            | Self::BooleanExpr(..)
+            | Self::IntExpr(..)
+            | Self::FloatExpr(..)
+            | Self::StringExpr(..) => vec![],
+            Self::VariableExpr(VariableExpr { name, .. }) => vec![Node::Ident(name)],
+            Self::ListExpr(ListExpr { values, .. }) => values.iter().map(Node::from_expr).collect(),
+            Self::ObjectExpr(ObjectExpr { pairs, .. }) => {
+                pairs.iter().map(Node::ObjectExprPair).collect()
+            }
+            Self::ObjectExprPair(ObjectExprPair { key, value, .. }) => vec![
+                match key {
+                    ObjectExprKeyVariant::Identifier(identifier) => Node::Ident(identifier),
+                    ObjectExprKeyVariant::String(string) => Node::StringExpr(string),
+                    ObjectExprKeyVariant::Expr(ObjectExprKeyExpr { value, .. }) => {
+                        Node::from_expr(value)
+                    }
+                },
+                Node::from_expr(value),
+            ],
+            Self::FunctionExpr(FunctionExpr {
+                name,
+                parameters,
+                body,
+                ..
+            }) => name
+                .iter()
+                .map(|name| Node::Ident(name))
+                .chain(parameters.iter().map(Node::Ident))
+                .chain(std::iter::once(match body {
+                    FunctionExprBody::Block(block) => Node::Block(block),
+                    FunctionExprBody::Expr(expr) => Node::from_expr(expr),
+                }))
+                .collect(),
+            Self::WrappedExpr(WrappedExpr { value, .. }) => vec![Node::from_expr(value)],
+            Self::IndexExpr(IndexExpr { target, index, .. }) => {
+                vec![Node::from_expr(target), Node::from_expr(index)]
+            }
+            Self::DotExpr(DotExpr {
+                target, property, ..
+            }) => vec![Node::from_expr(target), Node::Ident(property)],
+            Self::CallExpr(CallExpr {
+                target, arguments, ..
+            }) => std::iter::once(Node::from_expr(target))
+                .chain(arguments.iter().map(Node::from_expr))
+                .collect(),
+            Self::UnaryOperationExpr(UnaryOperationExpr { right, .. }) => {
+                vec![Node::from_expr(right)]
+            }
+            Self::BinaryOperationExpr(BinaryOperationExpr { left, right, .. }) => {
+                vec![Node::from_expr(left), Node::from_expr(right)]
+            }
+            // Statements
+            Self::IfStmt(IfStmt {
+                condition,
+                block,
+                else_clause,
+                ..
+            }) => {
+                let mut children = vec![Node::from_expr(condition), Node::Block(block)];
+                if let Some(else_clause) = else_clause {
+                    children.push(Node::ElseClause(else_clause));
+                }
+
+                children
+            }
+            Self::ElseClause(ElseClause { next, .. }) => vec![match next {
+                ElseClauseNextVariant::IfStmt(if_stmt) => Node::IfStmt(if_stmt),
+                ElseClauseNextVariant::Block(block) => Node::Block(block),
+            }],
+            Self::LoopStmt(LoopStmt { block, .. }) => vec![Node::Block(block)],
+            Self::WhileStmt(WhileStmt {
+                condition, block, ..
+            }) => vec![Node::from_expr(condition), Node::Block(block)],
+            Self::ReturnStmt(ReturnStmt { value, .. }) => {
+                value.iter().map(Node::from_expr).collect()
+            }
+            Self::BreakStmt(..) | Self::ContinueStmt(..) => vec![],
+            Self::FunctionStmt(FunctionDeclarationStmt { function, .. }) => {
+                vec![Node::FunctionExpr(function)]
+            }
+            Self::VariableDeclarationStmt(VariableDeclarationStmt { name, value, .. })
+            | Self::VariableAssignmentStmt(VariableAssignmentStmt { name, value, .. }) => {
+                vec![Node::Ident(name), Node::from_expr(value)]
+            }
+            Self::IndexAssignmentStmt(IndexAssignmentStmt {
+                index_expr, value, ..
+            }) => vec![Node::IndexExpr(index_expr), Node::from_expr(value)],
+            Self::DotAssignmentStmt(DotAssignmentStmt {
+                dot_expr, value, ..
+            }) => vec![Node::DotExpr(dot_expr), Node::from_expr(value)],
+            Self::ImportStmt(ImportStmt { variant, path, .. }) => {
+                let mut children = match variant {
+                    ImportStmtVariant::Named(specifiers) => {
+                        specifiers.iter().map(Node::ImportSpecifier).collect()
+                    }
+                    ImportStmtVariant::Namespace(name) => vec![Node::Ident(name)],
+                };
+                children.push(Node::StringExpr(path));
+                children
+            }
+            Self::ImportSpecifier(ImportSpecifier { name, alias, .. }) => {
+                std::iter::once(Node::Ident(name))
+                    .chain(alias.iter().map(|alias| Node::Ident(alias)))
+                    .collect()
+            }
+            Self::ExportFromStmt(ExportFromStmt {
+                specifiers, path, ..
+            }) => specifiers
+                .iter()
+                .map(Node::ImportSpecifier)
+                .chain(std::iter::once(Node::StringExpr(path)))
+                .collect(),
+            Self::ExprStmt(ExprStmt { expr, .. }) => vec![Node::from_expr(expr)],
+        }
+    }
 }
@@ -30,6 +30,14 @@ impl UnaryOperator {
             _ => return None,
         })
     }
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            Self::Neg => Symbol::Sub.text(),
+            Self::BitNot => Symbol::BitNot.text(),
+            Self::Not => Keyword::Not.text(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -105,6 +113,28 @@ impl BinaryOperator {
             Self::Or => 10,
         }
     }
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            Self::Add => Symbol::Add.text(),
+            Self::Sub => Symbol::Sub.text(),
+            Self::Mul => Symbol::Mul.text(),
+            Self::Div => Symbol::Div.text(),
+            Self::Shl => Symbol::Shl.text(),
+            Self::Shr => Symbol::Shr.text(),
+            Self::BitAnd => Symbol::BitAnd.text(),
+            Self::BitOr => Symbol::BitOr.text(),
+            Self::And => Keyword::And.text(),
+            Self::Or => Keyword::Or.text(),
+            Self::Ncl => Symbol::Ncl.text(),
+            Self::Lt => Symbol::Lt.text(),
+            Self::Gt => Symbol::Gt.text(),
+            Self::Lte => Symbol::Lte.text(),
+            Self::Gte => Symbol::Gte.text(),
+            Self::Eq => Symbol::Eq.text(),
+            Self::Neq => Symbol::Neq.text(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -136,4 +166,15 @@ impl AssignmentOperator {
             _ => return None,
         })
     }
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            AssignmentOperator::Assign => Symbol::Assign.text(),
+            AssignmentOperator::AddAssign => Symbol::AddAssign.text(),
+            AssignmentOperator::SubAssign => Symbol::SubAssign.text(),
+            AssignmentOperator::MulAssign => Symbol::MulAssign.text(),
+            AssignmentOperator::DivAssign => Symbol::DivAssign.text(),
+            AssignmentOperator::NclAssign => Symbol::NclAssign.text(),
+        }
+    }
 }
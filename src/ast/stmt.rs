@@ -1,5 +1,5 @@
 use super::base::{Block, Ident};
-use super::expr::{DotExpr, Expr, FunctionExpr, IndexExpr};
+use super::expr::{DotExpr, Expr, FunctionExpr, IndexExpr, StringExpr};
 use super::node::NodeInfo;
 use super::operator::AssignmentOperator;
 
@@ -16,6 +16,8 @@ pub enum Stmt {
     VariableAssignment(Box<VariableAssignmentStmt>),
     IndexAssignment(Box<IndexAssignmentStmt>),
     DotAssignment(Box<DotAssignmentStmt>),
+    Import(Box<ImportStmt>),
+    ExportFrom(Box<ExportFromStmt>),
     Expr(Box<ExprStmt>),
 }
 
@@ -107,6 +109,39 @@ pub struct DotAssignmentStmt {
     pub value: Expr,
 }
 
+#[derive(Debug)]
+pub struct ImportStmt {
+    pub info: NodeInfo,
+    pub variant: ImportStmtVariant,
+    pub path: Box<StringExpr>,
+}
+
+#[derive(Debug)]
+pub enum ImportStmtVariant {
+    Named(Vec<ImportSpecifier>),
+    Namespace(Box<Ident>),
+}
+
+#[derive(Debug)]
+pub struct ImportSpecifier {
+    pub info: NodeInfo,
+    pub name: Box<Ident>,
+    pub alias: Option<Box<Ident>>,
+}
+
+impl ImportSpecifier {
+    pub fn local_name(&self) -> &Ident {
+        self.alias.as_ref().unwrap_or(&self.name)
+    }
+}
+
+#[derive(Debug)]
+pub struct ExportFromStmt {
+    pub info: NodeInfo,
+    pub specifiers: Vec<ImportSpecifier>,
+    pub path: Box<StringExpr>,
+}
+
 #[derive(Debug)]
 pub struct ExprStmt {
     pub info: NodeInfo,
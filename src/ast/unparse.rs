@@ -0,0 +1,502 @@
+use std::fmt::{Display, Formatter, Result as FormatResult, Write};
+
+use super::base::*;
+use super::expr::*;
+use super::operator::*;
+use super::stmt::*;
+
+// Prints nodes back out as source code that parses to the same AST. Unlike the formatter this
+// doesn't need the original source, so it works for nodes built in Rust too. Those can contain
+// operations the parser would never produce without parentheses, e.g. "(1 + 2) * 3", so
+// parentheses are added where they're needed. They can also contain literals the parser never
+// produces, like negative numbers, which only round-trip to an equivalent AST, e.g. a negation.
+
+impl Display for Chunk {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        for stmt in &self.stmts {
+            writeln!(formatter, "{}", stmt)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Block {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        if self.stmts.is_empty() {
+            return write!(formatter, "{{}}");
+        }
+
+        writeln!(formatter, "{{")?;
+        let mut indented = Indented {
+            formatter,
+            line_start: true,
+        };
+        for stmt in &self.stmts {
+            writeln!(indented, "{}", stmt)?;
+        }
+        write!(formatter, "}}")
+    }
+}
+
+impl Display for Ident {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "{}", self.text)
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match self {
+            Expr::Null(expr) => expr.fmt(formatter),
+            Expr::Boolean(expr) => expr.fmt(formatter),
+            Expr::Int(expr) => expr.fmt(formatter),
+            Expr::Float(expr) => expr.fmt(formatter),
+            Expr::String(expr) => expr.fmt(formatter),
+            Expr::Variable(expr) => expr.fmt(formatter),
+            Expr::List(expr) => expr.fmt(formatter),
+            Expr::Object(expr) => expr.fmt(formatter),
+            Expr::Function(expr) => expr.fmt(formatter),
+            Expr::Wrapped(expr) => expr.fmt(formatter),
+            Expr::Index(expr) => expr.fmt(formatter),
+            Expr::Dot(expr) => expr.fmt(formatter),
+            Expr::Call(expr) => expr.fmt(formatter),
+            Expr::UnaryOperation(expr) => expr.fmt(formatter),
+            Expr::BinaryOperation(expr) => expr.fmt(formatter),
+        }
+    }
+}
+
+impl Display for NullExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "null")
+    }
+}
+
+impl Display for BooleanExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "{}", self.value)
+    }
+}
+
+impl Display for IntExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        // Negative numbers are written as a negation, so they're parsed back as one. That can't be
+        // done for the smallest int because its absolute value doesn't fit.
+        if self.value == i64::MIN {
+            return write!(formatter, "({} - 1)", i64::MIN + 1);
+        }
+
+        write!(formatter, "{}", self.value)
+    }
+}
+
+impl Display for FloatExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        if self.value.is_nan() {
+            return write!(formatter, "(0.0 / 0.0)");
+        }
+
+        if self.value.is_infinite() {
+            let sign = if self.value < 0.0 { "-" } else { "" };
+            return write!(formatter, "({}1.0 / 0.0)", sign);
+        }
+
+        // Float literals need a fractional part, otherwise they would be parsed as ints.
+        let text = self.value.to_string();
+        if text.contains('.') {
+            write!(formatter, "{}", text)
+        } else {
+            write!(formatter, "{}.0", text)
+        }
+    }
+}
+
+impl Display for StringExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        formatter.write_char('"')?;
+        for character in self.value.chars() {
+            match character {
+                '"' => formatter.write_str("\\\"")?,
+                '\\' => formatter.write_str("\\\\")?,
+                '\n' => formatter.write_str("\\n")?,
+                '\r' => formatter.write_str("\\r")?,
+                '\t' => formatter.write_str("\\t")?,
+                '\u{0008}' => formatter.write_str("\\b")?,
+                '\u{000C}' => formatter.write_str("\\f")?,
+                // Anything outside of ASCII is escaped too, the lexer measures literals in
+                // characters but slices them in bytes.
+                character if character.is_control() || !character.is_ascii() => {
+                    write!(formatter, "\\u{{{:x}}}", character as u32)?
+                }
+                character => formatter.write_char(character)?,
+            }
+        }
+        formatter.write_char('"')
+    }
+}
+
+impl Display for VariableExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "{}", self.name)
+    }
+}
+
+impl Display for ListExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "[")?;
+        write_list(formatter, &self.values)?;
+        write!(formatter, "]")
+    }
+}
+
+impl Display for ObjectExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        if self.pairs.is_empty() {
+            return write!(formatter, "{{}}");
+        }
+
+        write!(formatter, "{{ ")?;
+        write_list(formatter, &self.pairs)?;
+        write!(formatter, " }}")
+    }
+}
+
+impl Display for ObjectExprPair {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match &self.key {
+            ObjectExprKeyVariant::Identifier(ident) => write!(formatter, "{}", ident)?,
+            ObjectExprKeyVariant::String(string) => write!(formatter, "{}", string)?,
+            ObjectExprKeyVariant::Expr(key) => write!(formatter, "[{}]", key.value)?,
+        }
+
+        write!(formatter, ": {}", self.value)
+    }
+}
+
+impl Display for FunctionExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "fn")?;
+        if let Some(name) = &self.name {
+            write!(formatter, " {}", name)?;
+        }
+
+        write!(formatter, "(")?;
+        write_list(formatter, &self.parameters)?;
+        write!(formatter, ")")?;
+
+        match &self.body {
+            FunctionExprBody::Block(block) => write!(formatter, " {}", block),
+            FunctionExprBody::Expr(expr) => write!(formatter, " => {}", expr),
+        }
+    }
+}
+
+impl Display for WrappedExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "({})", self.value)
+    }
+}
+
+impl Display for IndexExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write_operand(formatter, &self.target, Operand::Target)?;
+        write!(formatter, "[{}]", self.index)
+    }
+}
+
+impl Display for DotExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write_operand(formatter, &self.target, Operand::Target)?;
+
+        // "1.a" would be read as a broken float.
+        if matches!(self.target, Expr::Int(..)) {
+            formatter.write_char(' ')?;
+        }
+
+        write!(formatter, ".{}", self.property)
+    }
+}
+
+impl Display for CallExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write_operand(formatter, &self.target, Operand::Target)?;
+        write!(formatter, "(")?;
+        write_list(formatter, &self.arguments)?;
+        write!(formatter, ")")
+    }
+}
+
+impl Display for UnaryOperationExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        let mut operand = String::new();
+        write_operand(&mut operand, &self.right, Operand::Unary)?;
+
+        // "not" is a word, and two minus signs are kept apart so they don't look like "--".
+        let spaced = match self.operator {
+            UnaryOperator::Not => true,
+            UnaryOperator::Neg => operand.starts_with('-'),
+            UnaryOperator::BitNot => false,
+        };
+
+        if spaced {
+            write!(formatter, "{} {}", self.operator.text(), operand)
+        } else {
+            write!(formatter, "{}{}", self.operator.text(), operand)
+        }
+    }
+}
+
+impl Display for BinaryOperationExpr {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        let precedence = self.operator.precedence();
+        write_operand(formatter, &self.left, Operand::Left(precedence))?;
+        write!(formatter, " {} ", self.operator.text())?;
+        write_operand(formatter, &self.right, Operand::Right(precedence))
+    }
+}
+
+impl Display for Stmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match self {
+            Stmt::If(stmt) => stmt.fmt(formatter),
+            Stmt::Loop(stmt) => stmt.fmt(formatter),
+            Stmt::While(stmt) => stmt.fmt(formatter),
+            Stmt::Return(stmt) => stmt.fmt(formatter),
+            Stmt::Break(stmt) => stmt.fmt(formatter),
+            Stmt::Continue(stmt) => stmt.fmt(formatter),
+            Stmt::FunctionDeclaration(stmt) => stmt.fmt(formatter),
+            Stmt::VariableDeclaration(stmt) => stmt.fmt(formatter),
+            Stmt::VariableAssignment(stmt) => stmt.fmt(formatter),
+            Stmt::IndexAssignment(stmt) => stmt.fmt(formatter),
+            Stmt::DotAssignment(stmt) => stmt.fmt(formatter),
+            Stmt::Import(stmt) => stmt.fmt(formatter),
+            Stmt::ExportFrom(stmt) => stmt.fmt(formatter),
+            Stmt::Expr(stmt) => stmt.fmt(formatter),
+        }
+    }
+}
+
+impl Display for IfStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "if {} {}", self.condition, self.block)?;
+        if let Some(else_clause) = &self.else_clause {
+            write!(formatter, " {}", else_clause)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for ElseClause {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match &self.next {
+            ElseClauseNextVariant::IfStmt(stmt) => write!(formatter, "else {}", stmt),
+            ElseClauseNextVariant::Block(block) => write!(formatter, "else {}", block),
+        }
+    }
+}
+
+impl Display for LoopStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "loop {}", self.block)
+    }
+}
+
+impl Display for WhileStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "while {} {}", self.condition, self.block)
+    }
+}
+
+impl Display for ReturnStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match &self.value {
+            Some(value) => write!(formatter, "return {};", value),
+            None => write!(formatter, "return;"),
+        }
+    }
+}
+
+impl Display for BreakStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "break;")
+    }
+}
+
+impl Display for ContinueStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "continue;")
+    }
+}
+
+impl Display for FunctionDeclarationStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        if self.is_exported {
+            write!(formatter, "export ")?;
+        }
+
+        // Exported functions can't be followed by a semicolon, other functions with an expression
+        // body have to be.
+        write!(formatter, "{}", self.function)?;
+        if !self.is_exported && matches!(self.function.body, FunctionExprBody::Expr(..)) {
+            write!(formatter, ";")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for VariableDeclarationStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        if self.is_exported {
+            write!(formatter, "export ")?;
+        }
+
+        write!(formatter, "let {} = {};", self.name, self.value)
+    }
+}
+
+impl Display for VariableAssignmentStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        let operator = self.operator.text();
+        write!(formatter, "{} {} {};", self.name, operator, self.value)
+    }
+}
+
+impl Display for IndexAssignmentStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        let operator = self.operator.text();
+        write!(
+            formatter,
+            "{} {} {};",
+            self.index_expr, operator, self.value
+        )
+    }
+}
+
+impl Display for DotAssignmentStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        let operator = self.operator.text();
+        write!(formatter, "{} {} {};", self.dot_expr, operator, self.value)
+    }
+}
+
+impl Display for ImportStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "import ")?;
+        match &self.variant {
+            ImportStmtVariant::Named(specifiers) => write_specifiers(formatter, specifiers)?,
+            ImportStmtVariant::Namespace(name) => write!(formatter, "* as {}", name)?,
+        }
+
+        write!(formatter, " from {};", self.path)
+    }
+}
+
+impl Display for ImportSpecifier {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match &self.alias {
+            Some(alias) => write!(formatter, "{} as {}", self.name, alias),
+            None => write!(formatter, "{}", self.name),
+        }
+    }
+}
+
+impl Display for ExportFromStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "export ")?;
+        write_specifiers(formatter, &self.specifiers)?;
+        write!(formatter, " from {};", self.path)
+    }
+}
+
+impl Display for ExprStmt {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        // A function on its own would be parsed as a function declaration.
+        if matches!(self.expr, Expr::Function(..)) {
+            return write!(formatter, "({});", self.expr);
+        }
+
+        write!(formatter, "{};", self.expr)
+    }
+}
+
+// Where an expression appears inside another one, which decides whether it needs parentheses.
+#[derive(Clone, Copy)]
+enum Operand {
+    Left(u8),
+    Right(u8),
+    Unary,
+    // The target of an index, a dot or a call.
+    Target,
+}
+
+fn write_operand<W: Write>(output: &mut W, expr: &Expr, operand: Operand) -> FormatResult {
+    let wrapped = match (expr, operand) {
+        // Operators with the same precedence are grouped from left to right.
+        (Expr::BinaryOperation(expr), Operand::Left(precedence)) => {
+            expr.operator.precedence() > precedence
+        }
+        (Expr::BinaryOperation(expr), Operand::Right(precedence)) => {
+            expr.operator.precedence() >= precedence
+        }
+        (Expr::BinaryOperation(..), _) => true,
+        (Expr::UnaryOperation(..), Operand::Target) => true,
+        (Expr::Int(expr), Operand::Target) => expr.value < 0,
+        (Expr::Float(expr), Operand::Target) => expr.value.is_sign_negative(),
+        // The body would take everything after it as part of the function.
+        (Expr::Function(expr), _) => matches!(expr.body, FunctionExprBody::Expr(..)),
+        _ => false,
+    };
+
+    if wrapped {
+        write!(output, "({})", expr)
+    } else {
+        write!(output, "{}", expr)
+    }
+}
+
+fn write_list<T: Display>(formatter: &mut Formatter<'_>, items: &[T]) -> FormatResult {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            write!(formatter, ", ")?;
+        }
+
+        write!(formatter, "{}", item)?;
+    }
+
+    Ok(())
+}
+
+fn write_specifiers(formatter: &mut Formatter<'_>, specifiers: &[ImportSpecifier]) -> FormatResult {
+    if specifiers.is_empty() {
+        return write!(formatter, "{{}}");
+    }
+
+    write!(formatter, "{{ ")?;
+    write_list(formatter, specifiers)?;
+    write!(formatter, " }}")
+}
+
+// Indents every line written through it, used for the statements in a block. Blocks nested in
+// those statements are indented again by their own writer.
+struct Indented<'a, 'b> {
+    formatter: &'a mut Formatter<'b>,
+    line_start: bool,
+}
+
+impl Write for Indented<'_, '_> {
+    fn write_str(&mut self, text: &str) -> FormatResult {
+        for line in text.split_inclusive('\n') {
+            if self.line_start && line != "\n" {
+                self.formatter.write_str("    ")?;
+            }
+
+            self.formatter.write_str(line)?;
+            self.line_start = line.ends_with('\n');
+        }
+
+        Ok(())
+    }
+}
@@ -1,5 +1,7 @@
 mod builder;
+mod disassembler;
 mod environment;
+mod import;
 mod instruction;
 mod module;
 mod procedure;
@@ -8,12 +10,15 @@ mod variable;
 use std::fmt::{Debug, Formatter, Result as FormatResult};
 
 pub use self::builder::Builder;
+pub use self::disassembler::Disassembler;
 pub use self::environment::Environment;
+pub use self::import::{ResolvedImports, ResolvedModule};
 pub use self::instruction::Instruction;
 pub use self::module::Module;
 pub use self::procedure::Procedure;
 pub use self::variable::{
-    ExportLocation, Parameter, StackLocation, Variable, VariableLocation, VariableVariant,
+    ExportLocation, ImportLocation, Parameter, StackLocation, Variable, VariableLocation,
+    VariableVariant,
 };
 
 use crate::source::Span;
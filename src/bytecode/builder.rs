@@ -7,13 +7,15 @@ mod stmt;
 use std::collections::{BTreeMap, HashSet};
 
 use crate::ast::NodeInfo;
+use crate::error::{RegisError, RegisErrorVariant};
 use crate::shared::SharedImmutable;
-use crate::source::Span;
+use crate::source::{Location, Span};
 
 use super::environment::Environment;
+use super::import::ResolvedImports;
 use super::instruction::Instruction;
-use super::variable::GlobalLocation;
-use super::{Bytecode, Variable, VariableLocation, VariableVariant};
+use super::variable::{GlobalLocation, ImportLocation};
+use super::{Bytecode, ExportLocation, Variable, VariableLocation, VariableVariant};
 
 use marker::Marker;
 
@@ -23,6 +25,9 @@ pub struct Builder<'environment> {
     spans: Vec<Span>,
     markers: BTreeMap<usize, HashSet<Marker>>,
     environment: &'environment mut Environment,
+    imports: Option<&'environment ResolvedImports>,
+    // The first error found while emitting, returned once the chunk is built.
+    error: Option<RegisError>,
 }
 
 impl<'environment> Builder<'environment> {
@@ -32,6 +37,18 @@ impl<'environment> Builder<'environment> {
             spans: Vec::new(),
             markers: BTreeMap::new(),
             environment,
+            imports: None,
+            error: None,
+        }
+    }
+
+    pub fn with_imports(
+        environment: &'environment mut Environment,
+        imports: &'environment ResolvedImports,
+    ) -> Self {
+        Self {
+            imports: Some(imports),
+            ..Self::new(environment)
         }
     }
 
@@ -134,6 +151,24 @@ impl<'environment> Builder<'environment> {
                     Instruction::PushExport(location.into())
                 }
             }
+            VariableLocation::Import(ImportLocation { module, export }) => {
+                if assign {
+                    self.add_error(
+                        origin,
+                        RegisErrorVariant::TypeError {
+                            message: format!("Imported variable '{}' cannot be reassigned.", name),
+                        },
+                    );
+                    return;
+                }
+
+                match export {
+                    Some(export) => {
+                        Instruction::PushExport(ExportLocation { module, export }.into())
+                    }
+                    None => Instruction::PushModule(module.into()),
+                }
+            }
             VariableLocation::Global(GlobalLocation { address }) => {
                 if assign {
                     panic!("Global variables cannot be reassigned.");
@@ -146,6 +181,17 @@ impl<'environment> Builder<'environment> {
         self.add(instruction, &origin);
     }
 
+    pub fn add_error(&mut self, origin: &NodeInfo, variant: RegisErrorVariant) {
+        if self.error.is_none() {
+            let location = Location::new(Some(self.environment.path().clone()), *origin.span());
+            self.error = Some(RegisError::new(Some(location), variant));
+        }
+    }
+
+    pub fn take_error(&mut self) -> Option<RegisError> {
+        self.error.take()
+    }
+
     pub fn build(mut self) -> Bytecode {
         self.finalize();
         Bytecode::new(self.instructions, self.spans)
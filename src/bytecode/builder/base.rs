@@ -1,16 +1,46 @@
 use crate::ast::*;
+use crate::error::{RegisError, RegisErrorVariant};
+use crate::source::Location;
 
+use super::super::import::ResolvedModule;
 use super::super::instruction::Instruction;
+use super::super::variable::ImportLocation;
 use super::Builder;
 
 impl<'environment> Builder<'environment> {
-    pub fn emit_chunk(&mut self, Chunk { stmts, .. }: &Chunk) {
+    pub fn emit_chunk(&mut self, chunk: &Chunk) -> Result<(), RegisError> {
+        self.emit_module_chunk(chunk, false).map(|_| ())
+    }
+
+    pub fn emit_result_chunk(&mut self, chunk: &Chunk) -> Result<bool, RegisError> {
+        self.emit_module_chunk(chunk, true)
+    }
+
+    fn emit_module_chunk(
+        &mut self,
+        Chunk { stmts, .. }: &Chunk,
+        keep_result: bool,
+    ) -> Result<bool, RegisError> {
+        // If the chunk ends with an expression statement and its result is wanted, leave the value
+        // of the expression on the stack instead of discarding it.
+        let has_result = keep_result && matches!(stmts.last(), Some(Stmt::Expr(..)));
+
+        self.register_imports(stmts)?;
         self.environment.push_scope();
         let stmts = self.hoist(stmts);
-        for stmt in stmts {
-            self.emit_stmt(&stmt);
+        let count = stmts.len();
+        for (i, stmt) in stmts.into_iter().enumerate() {
+            match stmt {
+                Stmt::Expr(stmt) if has_result && i == count - 1 => self.emit_expr(&stmt.expr),
+                _ => self.emit_stmt(stmt),
+            }
         }
         self.environment.pop_scope();
+
+        match self.take_error() {
+            Some(error) => Err(error),
+            None => Ok(has_result),
+        }
     }
 
     pub fn emit_block(&mut self, Block { stmts, .. }: &Block) {
@@ -35,11 +65,98 @@ impl<'environment> Builder<'environment> {
         self.add(Instruction::PushNull, info);
     }
 
+    pub fn resolved_import(
+        &self,
+        path: &StringExpr,
+    ) -> Result<&'environment ResolvedModule, RegisError> {
+        self.imports
+            .and_then(|imports| imports.get(&path.value))
+            .ok_or_else(|| {
+                RegisError::new(
+                    Some(Location::new(
+                        Some(self.environment.path().clone()),
+                        *path.info.span(),
+                    )),
+                    RegisErrorVariant::ModuleDoesNotExistError {
+                        path: path.value.to_string(),
+                    },
+                )
+            })
+    }
+
+    fn register_imports(&mut self, stmts: &[Stmt]) -> Result<(), RegisError> {
+        // Imports are resolved before the module is built, so the names they bind can be checked
+        // against the exports of the imported module here.
+        for stmt in stmts {
+            match stmt {
+                Stmt::Import(stmt) => {
+                    let module = self.resolved_import(&stmt.path)?;
+                    match &stmt.variant {
+                        ImportStmtVariant::Named(specifiers) => {
+                            for specifier in specifiers {
+                                self.check_import_specifier(module, specifier)?;
+                                self.environment.register_import_variable(
+                                    specifier.local_name().text.clone(),
+                                    ImportLocation {
+                                        module: module.module.clone(),
+                                        export: Some(specifier.name.text.clone()),
+                                    },
+                                );
+                            }
+                        }
+                        ImportStmtVariant::Namespace(name) => {
+                            self.environment.register_import_variable(
+                                name.text.clone(),
+                                ImportLocation {
+                                    module: module.module.clone(),
+                                    export: None,
+                                },
+                            );
+                        }
+                    }
+                }
+                Stmt::ExportFrom(stmt) => {
+                    let module = self.resolved_import(&stmt.path)?;
+                    for specifier in &stmt.specifiers {
+                        self.check_import_specifier(module, specifier)?;
+                        self.environment
+                            .register_export_variable(specifier.local_name().text.clone());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_import_specifier(
+        &self,
+        module: &ResolvedModule,
+        ImportSpecifier { info, name, .. }: &ImportSpecifier,
+    ) -> Result<(), RegisError> {
+        if module.exports.contains(&name.text) {
+            Ok(())
+        } else {
+            Err(RegisError::new(
+                Some(Location::new(
+                    Some(self.environment.path().clone()),
+                    *info.span(),
+                )),
+                RegisErrorVariant::ExportDoesNotExistError {
+                    path: module.module.to_string(),
+                    export: name.text.to_string(),
+                },
+            ))
+        }
+    }
+
     fn hoist<'b>(&mut self, stmts: &'b [Stmt]) -> Vec<&'b Stmt> {
         let mut result = stmts.iter().collect::<Vec<_>>();
         result.sort_by_key(|stmt| match stmt {
-            Stmt::FunctionDeclaration(..) => 0,
-            _ => 1,
+            Stmt::ExportFrom(..) => 0,
+            Stmt::FunctionDeclaration(..) => 1,
+            _ => 2,
         });
 
         for stmt in &result {
@@ -115,6 +115,10 @@ impl<'environment> Builder<'environment> {
             }
         }
 
+        if let Some(error) = builder.take_error() {
+            self.error.get_or_insert(error);
+        }
+
         let bytecode = builder.build();
         let procedure = Procedure::new(name, bytecode, environment);
         self.add(Instruction::CreateFunction(procedure.into()), info);
@@ -1,6 +1,7 @@
 use crate::ast::*;
 
 use super::super::instruction::Instruction;
+use super::super::variable::ExportLocation;
 use super::marker::Marker;
 use super::Builder;
 
@@ -18,6 +19,9 @@ impl<'environment> Builder<'environment> {
             Stmt::VariableAssignment(stmt) => self.emit_variable_assignment_stmt(stmt),
             Stmt::IndexAssignment(stmt) => self.emit_index_assignment_stmt(stmt),
             Stmt::DotAssignment(stmt) => self.emit_dot_assignment_stmt(stmt),
+            // Imported variables are bound when the chunk is built, so there's nothing to emit.
+            Stmt::Import(..) => {}
+            Stmt::ExportFrom(stmt) => self.emit_export_from_stmt(stmt),
             Stmt::Expr(stmt) => self.emit_expr_stmt(stmt),
         }
     }
@@ -219,6 +223,34 @@ impl<'environment> Builder<'environment> {
         self.add(Instruction::SetIndex, info);
     }
 
+    pub fn emit_export_from_stmt(
+        &mut self,
+        ExportFromStmt {
+            info,
+            specifiers,
+            path,
+        }: &ExportFromStmt,
+    ) {
+        let module = self
+            .resolved_import(path)
+            .expect("Re-exported module should have been resolved before it's emitted.");
+
+        // Copy each re-exported value into this module's exports.
+        for specifier in specifiers {
+            self.add(
+                Instruction::PushExport(
+                    ExportLocation {
+                        module: module.module.clone(),
+                        export: specifier.name.text.clone(),
+                    }
+                    .into(),
+                ),
+                info,
+            );
+            self.emit_variable_assign_instruction(&specifier.local_name().text, info);
+        }
+    }
+
     pub fn emit_expr_stmt(&mut self, ExprStmt { info, expr }: &ExprStmt) {
         self.emit_expr(expr);
         self.add(Instruction::Pop, info)
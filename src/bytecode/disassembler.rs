@@ -0,0 +1,210 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt::Write;
+
+use crate::shared::SharedImmutable;
+
+use super::environment::Environment;
+use super::instruction::Instruction;
+use super::module::Module;
+use super::procedure::Procedure;
+use super::{Bytecode, VariableVariant};
+
+#[derive(Debug)]
+pub struct Disassembler<'source> {
+    lines: Vec<usize>,
+    output: String,
+    procedures: VecDeque<SharedImmutable<Procedure>>,
+    source: &'source str,
+}
+
+impl<'source> Disassembler<'source> {
+    pub fn new(source: &'source str) -> Self {
+        // The byte offset each line starts at, used to find the line of an instruction's span.
+        let lines = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        Self {
+            lines,
+            output: String::new(),
+            procedures: VecDeque::new(),
+            source,
+        }
+    }
+
+    pub fn disassemble_module(mut self, module: &Module) -> String {
+        self.emit_header(&format!("module {}", module.path()), module.environment());
+        self.emit_bytecode(module.bytecode(), module.environment());
+        self.emit_procedures();
+        self.output
+    }
+
+    pub fn disassemble_procedure(mut self, procedure: &Procedure) -> String {
+        self.emit_procedure(procedure);
+        self.emit_procedures();
+        self.output
+    }
+
+    // Functions are listed after the code that creates them, in the order they're created.
+    fn emit_procedures(&mut self) {
+        while let Some(procedure) = self.procedures.pop_front() {
+            self.output.push('\n');
+            self.emit_procedure(&procedure);
+        }
+    }
+
+    fn emit_procedure(&mut self, procedure: &Procedure) {
+        let environment = procedure.environment();
+        let parameters = environment
+            .parameters()
+            .iter()
+            .map(|parameter| parameter.name.to_string())
+            .collect::<Vec<_>>();
+
+        let header = format!(
+            "function {}({})",
+            procedure_name(procedure),
+            parameters.join(", ")
+        );
+
+        self.emit_header(&header, environment);
+        self.emit_bytecode(procedure.bytecode(), environment);
+    }
+
+    fn emit_header(&mut self, header: &str, environment: &Environment) {
+        writeln!(self.output, "{}", header).unwrap();
+
+        let variables = (0..environment.frame_size())
+            .map(|address| variable_name(environment, address))
+            .collect::<Vec<_>>();
+        if !variables.is_empty() {
+            writeln!(self.output, "  variables: {}", variables.join(", ")).unwrap();
+        }
+
+        self.output.push('\n');
+    }
+
+    fn emit_bytecode(&mut self, bytecode: &Bytecode, environment: &Environment) {
+        let targets = bytecode
+            .instructions()
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::Jump(target)
+                | Instruction::JumpIf(target)
+                | Instruction::JumpUnless(target) => Some(*target),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        let labels = targets
+            .into_iter()
+            .enumerate()
+            .map(|(index, target)| (target, format!("L{}", index)))
+            .collect::<HashMap<_, _>>();
+
+        let mut previous_line = None;
+        for (offset, instruction) in bytecode.instructions().iter().enumerate() {
+            if let Some(label) = labels.get(&offset) {
+                writeln!(self.output, "{}:", label).unwrap();
+            }
+
+            // Only show the line number when it changes, so the statements stand out.
+            let line = bytecode
+                .spans()
+                .get(offset)
+                .map(|span| self.line(span.start()));
+            let line_column = match line {
+                Some(line) if line != previous_line.unwrap_or(0) => line.to_string(),
+                _ => String::new(),
+            };
+            previous_line = line;
+
+            let operand = self.operand(instruction, environment, &labels);
+            let line = format!(
+                "{:>6}  {:>5}  {:<16}{}",
+                offset,
+                line_column,
+                instruction_name(instruction),
+                operand
+            );
+            writeln!(self.output, "{}", line.trim_end()).unwrap();
+        }
+
+        // Jumps past the last instruction end the bytecode.
+        if let Some(label) = labels.get(&bytecode.instructions().len()) {
+            writeln!(self.output, "{}:", label).unwrap();
+        }
+    }
+
+    fn operand(
+        &mut self,
+        instruction: &Instruction,
+        environment: &Environment,
+        labels: &HashMap<usize, String>,
+    ) -> String {
+        match instruction {
+            Instruction::DuplicateTop(count)
+            | Instruction::CreateList(count)
+            | Instruction::CreateObject(count)
+            | Instruction::Call(count) => count.to_string(),
+            Instruction::Jump(target)
+            | Instruction::JumpIf(target)
+            | Instruction::JumpUnless(target) => labels[target].clone(),
+            Instruction::PushBoolean(value) => value.to_string(),
+            Instruction::PushInt(value) => value.to_string(),
+            Instruction::PushFloat(value) => format!("{:?}", value),
+            Instruction::PushString(value) => format!("{:?}", **value),
+            Instruction::PushVariable(address) | Instruction::AssignVariable(address) => {
+                format!("{} ({})", address, variable_name(environment, *address))
+            }
+            Instruction::PushExport(location) | Instruction::AssignExport(location) => {
+                format!("{} ({})", location.export, location.module)
+            }
+            Instruction::PushModule(module) => module.to_string(),
+            Instruction::PushGlobal(address) => match environment.globals().get_index(*address) {
+                Some(name) => format!("{} ({})", address, name),
+                None => address.to_string(),
+            },
+            Instruction::CreateFunction(procedure) => {
+                self.procedures.push_back(procedure.clone());
+                procedure_name(procedure)
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn line(&self, offset: usize) -> usize {
+        let offset = offset.min(self.source.len());
+        self.lines.partition_point(|start| *start <= offset)
+    }
+}
+
+fn instruction_name(instruction: &Instruction) -> String {
+    // The debug name of an instruction is its variant name followed by its operands, if any.
+    let name = format!("{:?}", instruction);
+    match name.find('(') {
+        Some(index) => name[..index].into(),
+        None => name,
+    }
+}
+
+fn procedure_name(procedure: &Procedure) -> String {
+    match procedure.name() {
+        Some(name) => name.to_string(),
+        None => "<anonymous>".into(),
+    }
+}
+
+fn variable_name(environment: &Environment, address: usize) -> String {
+    let parameters = environment.parameters();
+    if let Some(parameter) = parameters.get(address) {
+        return parameter.name.to_string();
+    }
+
+    match environment.variables().get(address - parameters.len()) {
+        Some(variable) => match &variable.variant {
+            VariableVariant::Local => variable.name.to_string(),
+            VariableVariant::Capture { .. } => format!("captured {}", variable.name),
+        },
+        None => "?".into(),
+    }
+}
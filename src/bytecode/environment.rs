@@ -3,11 +3,12 @@ use std::collections::HashMap;
 use indexmap::IndexSet;
 
 use crate::shared::SharedImmutable;
-use crate::source::CanonicalPath;
+use crate::source::{CanonicalPath, ModuleKey};
 
 use super::variable::GlobalLocation;
 use super::{
-    ExportLocation, Parameter, StackLocation, Variable, VariableLocation, VariableVariant,
+    ExportLocation, ImportLocation, Parameter, StackLocation, Variable, VariableLocation,
+    VariableVariant,
 };
 
 type Scope = HashMap<SharedImmutable<String>, usize>;
@@ -18,6 +19,7 @@ pub struct Environment {
     parent: Option<Box<Self>>,
     globals: IndexSet<SharedImmutable<String>>,
     exports: IndexSet<SharedImmutable<String>>,
+    imports: HashMap<SharedImmutable<String>, ImportLocation>,
     scopes: Vec<Scope>,
     parameters: Vec<Parameter>,
     variables: Vec<Variable>,
@@ -32,6 +34,7 @@ impl Environment {
             variables: Vec::new(),
             globals: IndexSet::new(),
             exports: IndexSet::new(),
+            imports: HashMap::new(),
             scopes: vec![Scope::new()],
         }
     }
@@ -55,6 +58,14 @@ impl Environment {
         }
     }
 
+    pub fn globals(&self) -> &IndexSet<SharedImmutable<String>> {
+        &self.globals
+    }
+
+    pub fn exports(&self) -> &IndexSet<SharedImmutable<String>> {
+        &self.exports
+    }
+
     pub fn parameters(&self) -> &Vec<Parameter> {
         &self.parameters
     }
@@ -119,6 +130,14 @@ impl Environment {
         self.exports.insert(name);
     }
 
+    pub fn register_import_variable(
+        &mut self,
+        name: SharedImmutable<String>,
+        location: ImportLocation,
+    ) {
+        self.imports.insert(name, location);
+    }
+
     pub fn register_global_variable(&mut self, name: SharedImmutable<String>) {
         self.globals.insert(name);
     }
@@ -165,7 +184,7 @@ impl Environment {
         // Check to see if it's an exported variable from the current environment.
         if self.exports.contains(name) {
             return Some(VariableLocation::Export(ExportLocation {
-                path: self.path.clone(),
+                module: ModuleKey::Path(self.path.clone()),
                 export: name.clone(),
             }));
         }
@@ -177,7 +196,7 @@ impl Environment {
             while let Some(ancestor) = current {
                 if ancestor.exports.contains(name) {
                     return Some(VariableLocation::Export(ExportLocation {
-                        path: ancestor.path.clone(),
+                        module: ModuleKey::Path(ancestor.path.clone()),
                         export: name.clone(),
                     }));
                 }
@@ -186,6 +205,19 @@ impl Environment {
             }
         }
 
+        // Check to see if it's a variable imported into the current or a containing environment.
+        {
+            let mut current = Some(self);
+
+            while let Some(environment) = current {
+                if let Some(location) = environment.imports.get(name) {
+                    return Some(VariableLocation::Import(location.clone()));
+                }
+
+                current = environment.parent.as_deref();
+            }
+        }
+
         // Check to see if the variable is global.
         if let Some(address) = self.globals.get_index_of(name) {
             return Some(VariableLocation::Global(GlobalLocation { address }));
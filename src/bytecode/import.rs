@@ -0,0 +1,14 @@
+use std::collections::HashMap;
+
+use indexmap::IndexSet;
+
+use crate::shared::SharedImmutable;
+use crate::source::ModuleKey;
+
+pub type ResolvedImports = HashMap<SharedImmutable<String>, ResolvedModule>;
+
+#[derive(Debug, Clone)]
+pub struct ResolvedModule {
+    pub module: ModuleKey,
+    pub exports: IndexSet<SharedImmutable<String>>,
+}
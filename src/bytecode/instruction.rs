@@ -1,4 +1,5 @@
 use crate::shared::SharedImmutable;
+use crate::source::ModuleKey;
 
 use super::procedure::Procedure;
 use super::ExportLocation;
@@ -23,6 +24,7 @@ pub enum Instruction {
     AssignVariable(usize),
     PushExport(Box<ExportLocation>),
     AssignExport(Box<ExportLocation>),
+    PushModule(Box<ModuleKey>),
     PushGlobal(usize),
     CreateList(usize),
     CreateObject(usize),
@@ -1,7 +1,9 @@
 use crate::ast::Chunk;
+use crate::error::RegisError;
 use crate::source::CanonicalPath;
 
 use super::environment::Environment;
+use super::import::ResolvedImports;
 use super::{Builder, Bytecode};
 
 #[derive(Debug)]
@@ -9,6 +11,7 @@ pub struct Module {
     path: CanonicalPath,
     bytecode: Bytecode,
     environment: Environment,
+    has_result: bool,
 }
 
 impl Module {
@@ -17,15 +20,37 @@ impl Module {
             path,
             bytecode,
             environment,
+            has_result: false,
         }
     }
 
-    pub fn build(path: CanonicalPath, chunk: &Chunk, mut environment: Environment) -> Self {
-        let mut builder = Builder::new(&mut environment);
-        builder.emit_chunk(chunk);
+    pub fn build(
+        path: CanonicalPath,
+        chunk: &Chunk,
+        mut environment: Environment,
+        imports: &ResolvedImports,
+    ) -> Result<Self, RegisError> {
+        let mut builder = Builder::with_imports(&mut environment, imports);
+        builder.emit_chunk(chunk)?;
         let bytecode = builder.build();
 
-        Self::new(path, bytecode, environment)
+        Ok(Self::new(path, bytecode, environment))
+    }
+
+    pub fn build_with_result(
+        path: CanonicalPath,
+        chunk: &Chunk,
+        mut environment: Environment,
+        imports: &ResolvedImports,
+    ) -> Result<Self, RegisError> {
+        let mut builder = Builder::with_imports(&mut environment, imports);
+        let has_result = builder.emit_result_chunk(chunk)?;
+        let bytecode = builder.build();
+
+        Ok(Self {
+            has_result,
+            ..Self::new(path, bytecode, environment)
+        })
     }
 
     pub fn path(&self) -> &CanonicalPath {
@@ -39,4 +64,8 @@ impl Module {
     pub fn environment(&self) -> &Environment {
         &self.environment
     }
+
+    pub fn has_result(&self) -> bool {
+        self.has_result
+    }
 }
@@ -1,5 +1,5 @@
 use crate::shared::SharedImmutable;
-use crate::source::CanonicalPath;
+use crate::source::ModuleKey;
 
 #[derive(Debug, Clone)]
 pub struct Parameter {
@@ -21,6 +21,7 @@ pub enum VariableVariant {
 pub enum VariableLocation {
     Stack(StackLocation),
     Export(ExportLocation),
+    Import(ImportLocation),
     Global(GlobalLocation),
 }
 
@@ -32,10 +33,16 @@ pub struct StackLocation {
 
 #[derive(Debug, Clone)]
 pub struct ExportLocation {
-    pub path: CanonicalPath,
+    pub module: ModuleKey,
     pub export: SharedImmutable<String>,
 }
 
+#[derive(Debug, Clone)]
+pub struct ImportLocation {
+    pub module: ModuleKey,
+    pub export: Option<SharedImmutable<String>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct GlobalLocation {
     pub address: usize,
@@ -36,9 +36,41 @@ pub enum RegisErrorVariant {
     ModuleDoesNotExistError {
         path: String,
     },
+    ExportDoesNotExistError {
+        path: String,
+        export: String,
+    },
+    CircularImportError {
+        chain: Vec<String>,
+    },
     ParseError {
         message: String,
     },
+    PermissionDenied {
+        message: String,
+    },
+    InstructionLimitError {
+        limit: usize,
+    },
+    CallDepthLimitError {
+        limit: usize,
+    },
+    StackLimitError {
+        limit: usize,
+    },
+    HeapLimitError {
+        limit: usize,
+    },
+    HeapSizeLimitError {
+        limit: usize,
+    },
+    Interrupted,
+    IOError {
+        message: String,
+    },
+    WorkerError {
+        message: String,
+    },
 }
 
 impl RegisError {
@@ -120,7 +152,36 @@ impl RegisError {
                 "Imported module at path '{}' does not exist.",
                 path,
             ),
+            RegisErrorVariant::ExportDoesNotExistError { path, export } => format!(
+                "Module '{}' does not export '{}'.",
+                path, export,
+            ),
+            RegisErrorVariant::CircularImportError { chain } => format!(
+                "Circular import detected. Import chain: {}.",
+                chain.join(" -> "),
+            ),
             RegisErrorVariant::ParseError { message } => format!("Invalid syntax. {}", message),
+            RegisErrorVariant::PermissionDenied { message } => {
+                format!("Permission denied. {}", message)
+            }
+            RegisErrorVariant::InstructionLimitError { limit } => {
+                format!("Exceeded the limit of {} instructions.", limit)
+            }
+            RegisErrorVariant::CallDepthLimitError { limit } => {
+                format!("Exceeded the maximum call depth of {}.", limit)
+            }
+            RegisErrorVariant::StackLimitError { limit } => {
+                format!("Exceeded the maximum stack size of {} values.", limit)
+            }
+            RegisErrorVariant::HeapLimitError { limit } => {
+                format!("Exceeded the limit of {} heap allocations.", limit)
+            }
+            RegisErrorVariant::HeapSizeLimitError { limit } => {
+                format!("Exceeded the heap size limit of {} bytes.", limit)
+            }
+            RegisErrorVariant::Interrupted => "Execution was interrupted.".into(),
+            RegisErrorVariant::IOError { message } => message.into(),
+            RegisErrorVariant::WorkerError { message } => message.into(),
         }
     }
 
@@ -138,7 +199,7 @@ impl RegisError {
 
         let bytes = source.as_bytes();
         let code = {
-            let mut start = span.start().min(bytes.len() - 1).max(0);
+            let mut start = span.start().min(bytes.len().saturating_sub(1));
             let mut end = start;
 
             while start > 0 && !is_newline(source, start) {
@@ -0,0 +1,689 @@
+mod doc;
+
+use crate::ast::*;
+use crate::lexer::{Lexer, TokenKind};
+use crate::parser::{ParseResult, Parser};
+use crate::source::Span;
+
+use self::doc::{print, Doc};
+
+const MAX_WIDTH: usize = 100;
+
+// Formats a module by printing its AST back out. The parser throws comments away, so they're
+// collected from the tokens up front and placed back in front of (or after) the nearest node.
+pub struct Formatter<'source> {
+    source: &'source str,
+    tokens: Vec<Span>,
+    comments: Vec<Span>,
+    next_comment: usize,
+}
+
+impl<'source> Formatter<'source> {
+    pub fn new(source: &'source str) -> Self {
+        let mut tokens = Vec::new();
+        let mut comments = Vec::new();
+        for token in Lexer::new(source) {
+            match token.kind() {
+                TokenKind::Whitespace => {}
+                TokenKind::Comment => comments.push(*token.span()),
+                _ => tokens.push(*token.span()),
+            }
+        }
+
+        Self {
+            source,
+            tokens,
+            comments,
+            next_comment: 0,
+        }
+    }
+
+    pub fn format(mut self) -> ParseResult<'source, String> {
+        let chunk = Parser::new(self.source).parse()?;
+        let doc = self.stmts(&chunk.stmts, self.source.len());
+        let output = print(&doc, MAX_WIDTH);
+        if output.trim().is_empty() {
+            return Ok(String::new());
+        }
+
+        Ok(output)
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt], end: usize) -> Doc {
+        let mut docs = Vec::new();
+        let mut previous = None;
+
+        for stmt in stmts {
+            let span = *Node::from_stmt(stmt).info().span();
+            while let Some(comment) = self.comment_before(span.start()) {
+                self.separate(&mut docs, previous, comment.start());
+                docs.push(self.comment(comment));
+                previous = Some(comment.end());
+            }
+
+            self.separate(&mut docs, previous, span.start());
+            docs.push(self.stmt(stmt));
+            previous = Some(self.trailing_comments(&mut docs, span, end));
+        }
+
+        while let Some(comment) = self.comment_before(end) {
+            self.separate(&mut docs, previous, comment.start());
+            docs.push(self.comment(comment));
+            previous = Some(comment.end());
+        }
+
+        Doc::Concat(docs)
+    }
+
+    // Starts a new line, keeping a single blank line if there was at least one in the source.
+    fn separate(&self, docs: &mut Vec<Doc>, previous: Option<usize>, next: usize) {
+        if let Some(previous) = previous {
+            // Only the whitespace right before the next node counts, a comment might have been
+            // moved here from further back.
+            let index = self.tokens.partition_point(|token| token.end() <= next);
+            let start = index
+                .checked_sub(1)
+                .map_or(previous, |index| previous.max(self.tokens[index].end()));
+
+            docs.push(Doc::HardLine);
+            if self.source[start..next].matches('\n').count() > 1 {
+                docs.push(Doc::HardLine);
+            }
+        }
+    }
+
+    fn block(&mut self, block: &Block) -> Doc {
+        let close = self.code_end(block.info.span()) - 1;
+        if block.stmts.is_empty() && !self.has_comment_before(close) {
+            return Doc::text("{}");
+        }
+
+        Doc::Group(
+            vec![
+                Doc::text("{"),
+                Doc::indent(Doc::Concat(vec![
+                    Doc::HardLine,
+                    self.stmts(&block.stmts, close),
+                ])),
+                Doc::HardLine,
+                Doc::text("}"),
+            ],
+            true,
+        )
+    }
+
+    // Prints comma separated items on one line if they fit, otherwise one per line with a trailing
+    // comma. Comments between the items always break the line, and so do items that span multiple
+    // lines unless it's the last item in brackets or parentheses, e.g. a function passed last.
+    fn sequence<T>(
+        &mut self,
+        delimiters: (&'static str, &'static str),
+        close: usize,
+        padded: bool,
+        items: &[T],
+        span: impl Fn(&T) -> Span,
+        render: impl Fn(&mut Self, &T) -> Doc,
+    ) -> Doc {
+        let (open_text, close_text) = delimiters;
+        let mut broken = self.has_comment_before(close);
+        if items.is_empty() && !broken {
+            return Doc::text(format!("{}{}", open_text, close_text));
+        }
+
+        let line = || if padded { Doc::Line } else { Doc::SoftLine };
+        let mut docs = Vec::new();
+        for (index, item) in items.iter().enumerate() {
+            let span = span(item);
+            docs.push(if index == 0 { line() } else { Doc::Line });
+            while let Some(comment) = self.comment_before(span.start()) {
+                docs.push(self.comment(comment));
+                docs.push(Doc::HardLine);
+            }
+
+            let doc = render(self, item);
+            broken |= doc.is_multiline() && (padded || index < items.len() - 1);
+            docs.push(doc);
+            docs.push(if index == items.len() - 1 {
+                Doc::IfBreak(",")
+            } else {
+                Doc::text(",")
+            });
+            self.trailing_comments(&mut docs, span, close);
+        }
+
+        while let Some(comment) = self.comment_before(close) {
+            docs.push(Doc::HardLine);
+            docs.push(self.comment(comment));
+        }
+
+        Doc::Group(
+            vec![
+                Doc::text(open_text),
+                Doc::indent(Doc::Concat(docs)),
+                line(),
+                Doc::text(close_text),
+            ],
+            broken,
+        )
+    }
+
+    // Places the comments inside a node that couldn't be put anywhere else on their own lines after
+    // it, followed by a comment on the same line as the end of the node. Returns the end of the
+    // last thing printed.
+    fn trailing_comments(&mut self, docs: &mut Vec<Doc>, span: Span, limit: usize) -> usize {
+        let mut end = self.code_end(&span);
+        let mut inner = false;
+        while let Some(comment) = self.comment_before(end) {
+            docs.push(Doc::HardLine);
+            docs.push(self.comment(comment));
+            inner = true;
+        }
+
+        if let Some(comment) = self.comments.get(self.next_comment).copied() {
+            if comment.start() >= end
+                && comment.start() < limit
+                && !self.source[end..comment.start()].contains('\n')
+            {
+                self.next_comment += 1;
+                docs.push(if inner { Doc::HardLine } else { Doc::text(" ") });
+                docs.push(self.comment(comment));
+                end = comment.end();
+            }
+        }
+
+        end
+    }
+
+    fn comment(&self, comment: Span) -> Doc {
+        Doc::text(self.source[comment.start()..comment.end()].trim_end())
+    }
+
+    fn comment_before(&mut self, position: usize) -> Option<Span> {
+        let comment = self
+            .comments
+            .get(self.next_comment)
+            .filter(|comment| comment.start() < position)
+            .copied()?;
+        self.next_comment += 1;
+        Some(comment)
+    }
+
+    fn has_comment_before(&self, position: usize) -> bool {
+        self.comments
+            .get(self.next_comment)
+            .is_some_and(|comment| comment.start() < position)
+    }
+
+    // Node spans run up to the next token, so they include any whitespace and comments after the
+    // node. This finds the end of the node's last token instead.
+    fn code_end(&self, span: &Span) -> usize {
+        let index = self
+            .tokens
+            .partition_point(|token| token.start() < span.end());
+        match index.checked_sub(1).map(|index| self.tokens[index]) {
+            Some(token) if token.start() >= span.start() => token.end(),
+            _ => span.start(),
+        }
+    }
+
+    // The text of a token is kept as it was written, e.g. the escapes in a string.
+    fn token_text(&self, span: &Span) -> Doc {
+        Doc::text(&self.source[span.start()..self.code_end(span)])
+    }
+
+    // The position of the first token with the given text at or after a position.
+    fn find_token(&self, position: usize, text: &str) -> usize {
+        self.tokens[self
+            .tokens
+            .partition_point(|token| token.start() < position)..]
+            .iter()
+            .find(|token| &self.source[token.start()..token.end()] == text)
+            .map_or(position, |token| token.start())
+    }
+
+    fn stmt(&mut self, stmt: &Stmt) -> Doc {
+        match stmt {
+            Stmt::If(stmt) => self.if_stmt(stmt),
+            Stmt::Loop(stmt) => Doc::Concat(vec![Doc::text("loop "), self.block(&stmt.block)]),
+            Stmt::While(stmt) => Doc::Concat(vec![
+                Doc::text("while "),
+                self.expr(&stmt.condition),
+                Doc::text(" "),
+                self.block(&stmt.block),
+            ]),
+            Stmt::Return(stmt) => match &stmt.value {
+                Some(value) => {
+                    Doc::Concat(vec![Doc::text("return "), self.expr(value), Doc::text(";")])
+                }
+                None => Doc::text("return;"),
+            },
+            Stmt::Break(..) => Doc::text("break;"),
+            Stmt::Continue(..) => Doc::text("continue;"),
+            Stmt::FunctionDeclaration(stmt) => {
+                let mut docs = Vec::new();
+                if stmt.is_exported {
+                    docs.push(Doc::text("export "));
+                }
+
+                docs.push(self.function_expr(&stmt.function));
+
+                // Exported functions can't be followed by a semicolon, other functions with an
+                // expression body have to be.
+                if !stmt.is_exported && matches!(stmt.function.body, FunctionExprBody::Expr(..)) {
+                    docs.push(Doc::text(";"));
+                }
+
+                Doc::Concat(docs)
+            }
+            Stmt::VariableDeclaration(stmt) => Doc::Concat(vec![
+                Doc::text(if stmt.is_exported {
+                    "export let "
+                } else {
+                    "let "
+                }),
+                Doc::text(stmt.name.text.as_str()),
+                Doc::text(" = "),
+                self.expr(&stmt.value),
+                Doc::text(";"),
+            ]),
+            Stmt::VariableAssignment(stmt) => Doc::Concat(vec![
+                Doc::text(stmt.name.text.as_str()),
+                Doc::text(format!(" {} ", stmt.operator.text())),
+                self.expr(&stmt.value),
+                Doc::text(";"),
+            ]),
+            Stmt::IndexAssignment(stmt) => Doc::Concat(vec![
+                self.index_expr(&stmt.index_expr),
+                Doc::text(format!(" {} ", stmt.operator.text())),
+                self.expr(&stmt.value),
+                Doc::text(";"),
+            ]),
+            Stmt::DotAssignment(stmt) => Doc::Concat(vec![
+                self.dot_expr(&stmt.dot_expr),
+                Doc::text(format!(" {} ", stmt.operator.text())),
+                self.expr(&stmt.value),
+                Doc::text(";"),
+            ]),
+            Stmt::Import(stmt) => {
+                let variant = match &stmt.variant {
+                    ImportStmtVariant::Named(specifiers) => {
+                        self.import_specifiers(stmt.info.span().start(), specifiers)
+                    }
+                    ImportStmtVariant::Namespace(name) => Doc::text(format!("* as {}", name.text)),
+                };
+
+                Doc::Concat(vec![
+                    Doc::text("import "),
+                    variant,
+                    Doc::text(" from "),
+                    self.token_text(stmt.path.info.span()),
+                    Doc::text(";"),
+                ])
+            }
+            Stmt::ExportFrom(stmt) => Doc::Concat(vec![
+                Doc::text("export "),
+                self.import_specifiers(stmt.info.span().start(), &stmt.specifiers),
+                Doc::text(" from "),
+                self.token_text(stmt.path.info.span()),
+                Doc::text(";"),
+            ]),
+            Stmt::Expr(stmt) => Doc::Concat(vec![self.expr(&stmt.expr), Doc::text(";")]),
+        }
+    }
+
+    fn if_stmt(&mut self, stmt: &IfStmt) -> Doc {
+        let mut docs = vec![
+            Doc::text("if "),
+            self.expr(&stmt.condition),
+            Doc::text(" "),
+            self.block(&stmt.block),
+        ];
+
+        if let Some(else_clause) = &stmt.else_clause {
+            docs.push(Doc::text(" else "));
+            docs.push(match &else_clause.next {
+                ElseClauseNextVariant::IfStmt(stmt) => self.if_stmt(stmt),
+                ElseClauseNextVariant::Block(block) => self.block(block),
+            });
+        }
+
+        Doc::Concat(docs)
+    }
+
+    fn import_specifiers(&mut self, start: usize, specifiers: &[ImportSpecifier]) -> Doc {
+        let close = self.find_token(start, "}");
+        self.sequence(
+            ("{", "}"),
+            close,
+            true,
+            specifiers,
+            |specifier| *specifier.info.span(),
+            |_, specifier| match &specifier.alias {
+                Some(alias) => Doc::text(format!("{} as {}", specifier.name.text, alias.text)),
+                None => Doc::text(specifier.name.text.as_str()),
+            },
+        )
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Doc {
+        match expr {
+            Expr::Null(..) => Doc::text("null"),
+            Expr::Boolean(expr) => Doc::text(if expr.value { "true" } else { "false" }),
+            Expr::Int(expr) => self.token_text(expr.info.span()),
+            Expr::Float(expr) => self.token_text(expr.info.span()),
+            Expr::String(expr) => self.token_text(expr.info.span()),
+            Expr::Variable(expr) => Doc::text(expr.name.text.as_str()),
+            Expr::List(expr) => {
+                let close = self.code_end(expr.info.span()) - 1;
+                self.sequence(
+                    ("[", "]"),
+                    close,
+                    false,
+                    &expr.values,
+                    |value| *value.info().span(),
+                    Self::expr,
+                )
+            }
+            Expr::Object(expr) => {
+                let close = self.code_end(expr.info.span()) - 1;
+                self.sequence(
+                    ("{", "}"),
+                    close,
+                    true,
+                    &expr.pairs,
+                    |pair| *pair.info.span(),
+                    Self::object_pair,
+                )
+            }
+            Expr::Function(expr) => self.function_expr(expr),
+            Expr::Wrapped(expr) => {
+                Doc::Concat(vec![Doc::text("("), self.expr(&expr.value), Doc::text(")")])
+            }
+            Expr::Index(expr) => self.index_expr(expr),
+            Expr::Dot(expr) => self.dot_expr(expr),
+            Expr::Call(expr) => {
+                let target = self.expr(&expr.target);
+                let close = self.code_end(expr.info.span()) - 1;
+                let arguments = self.sequence(
+                    ("(", ")"),
+                    close,
+                    false,
+                    &expr.arguments,
+                    |argument| *argument.info().span(),
+                    Self::expr,
+                );
+                Doc::Concat(vec![target, arguments])
+            }
+            Expr::UnaryOperation(expr) => {
+                // Keep a space between "not" and its operand, and between two minus signs.
+                let operator = match (&expr.operator, &expr.right) {
+                    (UnaryOperator::Not, _) => format!("{} ", expr.operator.text()),
+                    (UnaryOperator::Neg, Expr::UnaryOperation(right))
+                        if right.operator == UnaryOperator::Neg =>
+                    {
+                        format!("{} ", expr.operator.text())
+                    }
+                    _ => expr.operator.text().into(),
+                };
+                Doc::Concat(vec![Doc::text(operator), self.expr(&expr.right)])
+            }
+            Expr::BinaryOperation(expr) => self.binary_operation(expr),
+        }
+    }
+
+    // Prints a chain of operators with the same precedence, e.g. `a + b - c`, on one line if it
+    // fits, otherwise with every operator starting a new line. Comments between the operands break
+    // the line and are kept in front of the operand that follows them.
+    fn binary_operation(&mut self, expr: &BinaryOperationExpr) -> Doc {
+        let mut chain = vec![expr];
+        while let Expr::BinaryOperation(left) = &chain[chain.len() - 1].left {
+            if left.operator.precedence() != expr.operator.precedence() {
+                break;
+            }
+
+            chain.push(left);
+        }
+
+        let first = self.expr(&chain[chain.len() - 1].left);
+        let mut broken = false;
+        let mut docs = Vec::new();
+        for expr in chain.iter().rev() {
+            docs.push(Doc::Line);
+            while let Some(comment) = self.comment_before(expr.right.info().span().start()) {
+                docs.push(self.comment(comment));
+                docs.push(Doc::HardLine);
+                broken = true;
+            }
+
+            docs.push(Doc::text(format!("{} ", expr.operator.text())));
+            docs.push(self.expr(&expr.right));
+        }
+
+        Doc::Group(vec![first, Doc::indent(Doc::Concat(docs))], broken)
+    }
+
+    fn object_pair(&mut self, pair: &ObjectExprPair) -> Doc {
+        let key = match &pair.key {
+            ObjectExprKeyVariant::Identifier(ident) => Doc::text(ident.text.as_str()),
+            ObjectExprKeyVariant::String(string) => self.token_text(string.info.span()),
+            ObjectExprKeyVariant::Expr(key) => {
+                Doc::Concat(vec![Doc::text("["), self.expr(&key.value), Doc::text("]")])
+            }
+        };
+
+        Doc::Concat(vec![key, Doc::text(": "), self.expr(&pair.value)])
+    }
+
+    fn function_expr(&mut self, expr: &FunctionExpr) -> Doc {
+        let mut docs = vec![Doc::text("fn")];
+        if let Some(name) = &expr.name {
+            docs.push(Doc::text(format!(" {}", name.text)));
+        }
+
+        // Parameters are always printed in parentheses, even though they're optional when there
+        // aren't any.
+        let body_start = match &expr.body {
+            FunctionExprBody::Block(block) => block.info.span().start(),
+            FunctionExprBody::Expr(..) => self.find_token(expr.info.span().start(), "=>"),
+        };
+        let close = match expr.parameters.last() {
+            Some(parameter) => self.find_token(parameter.info.span().start(), ")"),
+            None => body_start,
+        };
+        docs.push(self.sequence(
+            ("(", ")"),
+            close,
+            false,
+            &expr.parameters,
+            |parameter| *parameter.info.span(),
+            |_, parameter| Doc::text(parameter.text.as_str()),
+        ));
+
+        match &expr.body {
+            FunctionExprBody::Block(block) => {
+                docs.push(Doc::text(" "));
+                docs.push(self.block(block));
+            }
+            FunctionExprBody::Expr(value) => {
+                docs.push(Doc::text(" => "));
+                docs.push(self.expr(value));
+            }
+        }
+
+        Doc::Concat(docs)
+    }
+
+    fn index_expr(&mut self, expr: &IndexExpr) -> Doc {
+        Doc::Concat(vec![
+            self.expr(&expr.target),
+            Doc::text("["),
+            self.expr(&expr.index),
+            Doc::text("]"),
+        ])
+    }
+
+    fn dot_expr(&mut self, expr: &DotExpr) -> Doc {
+        Doc::Concat(vec![
+            self.expr(&expr.target),
+            Doc::text(format!(".{}", expr.property.text)),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(source: &str) -> String {
+        let formatted = Formatter::new(source)
+            .format()
+            .unwrap_or_else(|error| panic!("{}", error));
+
+        // Formatting has to be stable, otherwise --check would fail on formatted files.
+        let again = Formatter::new(&formatted)
+            .format()
+            .unwrap_or_else(|error| panic!("{}", error));
+        assert_eq!(formatted, again);
+
+        formatted
+    }
+
+    #[test]
+    fn spacing() {
+        assert_eq!(
+            format("let x=-1+2*(3-y) ;fn f(a,b)=>a??b;\n@println( x ,f(1,not true));"),
+            "let x = -1 + 2 * (3 - y);\nfn f(a, b) => a ?? b;\n@println(x, f(1, not true));\n"
+        );
+        assert_eq!(
+            format("export let o={a:[1,2,],\"b\":{},[k]:null};\nimport{a,b as c}from\"./x\";"),
+            "export let o = { a: [1, 2], \"b\": {}, [k]: null };\nimport { a, b as c } from \"./x\";\n"
+        );
+    }
+
+    #[test]
+    fn indentation() {
+        assert_eq!(
+            format("fn f(x){if x{return 1;}else if not x{loop{break;}}else{while x{x-=1;}}}"),
+            [
+                "fn f(x) {",
+                "    if x {",
+                "        return 1;",
+                "    } else if not x {",
+                "        loop {",
+                "            break;",
+                "        }",
+                "    } else {",
+                "        while x {",
+                "            x -= 1;",
+                "        }",
+                "    }",
+                "}",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            format("f(1, fn() { g(); });\nfn h {}"),
+            "f(1, fn() {\n    g();\n});\nfn h() {}\n"
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let source = [
+            "# First.",
+            "",
+            "",
+            "let x = 1;   # Trailing.",
+            "fn f() {",
+            "  # Leading.",
+            "  return [",
+            "    1, # One.",
+            "    # Two.",
+            "    2",
+            "  ];",
+            "",
+            "  # Last.",
+            "}",
+        ]
+        .join("\n");
+
+        assert_eq!(
+            format(&source),
+            [
+                "# First.",
+                "",
+                "let x = 1; # Trailing.",
+                "fn f() {",
+                "    # Leading.",
+                "    return [",
+                "        1, # One.",
+                "        # Two.",
+                "        2,",
+                "    ];",
+                "",
+                "    # Last.",
+                "}",
+                "",
+            ]
+            .join("\n")
+        );
+
+        // Comments inside an expression stay where they were.
+        assert_eq!(
+            format("let x = a + # Middle.\n  b + c;"),
+            "let x = a\n    # Middle.\n    + b\n    + c;\n"
+        );
+    }
+
+    #[test]
+    fn wrapping() {
+        let source =
+            "let long_list = [first_long_name, second_long_name, third_long_name, fourth_long_name, fifth_long_name];";
+        assert_eq!(
+            format(source),
+            [
+                "let long_list = [",
+                "    first_long_name,",
+                "    second_long_name,",
+                "    third_long_name,",
+                "    fourth_long_name,",
+                "    fifth_long_name,",
+                "];",
+                "",
+            ]
+            .join("\n")
+        );
+
+        // Operator chains are broken before each operator, nested ones only if they don't fit.
+        let source = "let total = first_long_operand + second_long_operand * factor - third_long_operand + fourth_long_operand;";
+        assert_eq!(
+            format(source),
+            [
+                "let total = first_long_operand",
+                "    + second_long_operand * factor",
+                "    - third_long_operand",
+                "    + fourth_long_operand;",
+                "",
+            ]
+            .join("\n")
+        );
+
+        // Only the sequences that don't fit are broken.
+        let source = "object.method(some_argument, another_argument, [1, 2, 3], { key: value, other: 2 }, the_last_argument_here);";
+        assert_eq!(
+            format(source),
+            [
+                "object.method(",
+                "    some_argument,",
+                "    another_argument,",
+                "    [1, 2, 3],",
+                "    { key: value, other: 2 },",
+                "    the_last_argument_here,",
+                ");",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+}
@@ -0,0 +1,161 @@
+// A document describes formatted code without committing to where lines break. Groups are printed
+// on a single line if they fit in the remaining width, otherwise every line in them is broken.
+#[derive(Debug)]
+pub enum Doc {
+    Text(String),
+    // A space, or a line break if the enclosing group is broken.
+    Line,
+    // Nothing, or a line break if the enclosing group is broken.
+    SoftLine,
+    // Always a line break, e.g. between statements.
+    HardLine,
+    Concat(Vec<Doc>),
+    Indent(Box<Doc>),
+    // The flag forces the group to break, e.g. because it contains comments.
+    Group(Vec<Doc>, bool),
+    // Text that is only printed if the enclosing group is broken, e.g. trailing commas.
+    IfBreak(&'static str),
+}
+
+impl Doc {
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        Doc::Text(text.into())
+    }
+
+    pub fn indent(doc: Doc) -> Self {
+        Doc::Indent(doc.into())
+    }
+
+    // Whether the doc always spans multiple lines, e.g. because it contains a block.
+    pub fn is_multiline(&self) -> bool {
+        match self {
+            Doc::HardLine | Doc::Group(_, true) => true,
+            Doc::Concat(docs) | Doc::Group(docs, false) => docs.iter().any(Doc::is_multiline),
+            Doc::Indent(doc) => doc.is_multiline(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+pub const INDENT: &str = "    ";
+
+pub fn print(doc: &Doc, width: usize) -> String {
+    let mut output = String::new();
+    let mut column = 0;
+    let mut commands = vec![(0, Mode::Break, doc)];
+
+    while let Some((indent, mode, doc)) = commands.pop() {
+        match doc {
+            Doc::Text(text) => {
+                output.push_str(text);
+                column = match text.rfind('\n') {
+                    Some(index) => text[index + 1..].chars().count(),
+                    None => column + text.chars().count(),
+                };
+            }
+            Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                if matches!(doc, Doc::Line) {
+                    output.push(' ');
+                    column += 1;
+                }
+            }
+            Doc::Line | Doc::SoftLine | Doc::HardLine => {
+                // Blank lines and lines that end in a space are left without trailing whitespace.
+                output.truncate(output.trim_end_matches(' ').len());
+                output.push('\n');
+                output.push_str(&INDENT.repeat(indent));
+                column = indent * INDENT.len();
+            }
+            Doc::Concat(docs) => {
+                commands.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+            }
+            // Only broken lines are indented. Anything that breaks inside a flat group, e.g. the body
+            // of a function passed as an argument, is indented from the start of the line instead.
+            Doc::Indent(doc) if mode == Mode::Flat => commands.push((indent, mode, doc)),
+            Doc::Indent(doc) => commands.push((indent + 1, mode, doc)),
+            Doc::Group(docs, broken) => {
+                let mode = if *broken {
+                    Mode::Break
+                } else if mode == Mode::Flat || fits(docs, &commands, width.saturating_sub(column))
+                {
+                    Mode::Flat
+                } else {
+                    Mode::Break
+                };
+                commands.extend(docs.iter().rev().map(|doc| (indent, mode, doc)));
+            }
+            Doc::IfBreak(text) => {
+                if mode == Mode::Break {
+                    output.push_str(text);
+                    column += text.len();
+                }
+            }
+        }
+    }
+
+    output.truncate(output.trim_end().len());
+    output.push('\n');
+    output
+}
+
+// Checks whether a group fits on the rest of the line if it isn't broken. Whatever follows the
+// group up to the next line break has to fit as well.
+fn fits(docs: &[Doc], rest: &[(usize, Mode, &Doc)], width: usize) -> bool {
+    let mut remaining = width as isize;
+    let mut stack = docs
+        .iter()
+        .rev()
+        .map(|doc| (Mode::Flat, doc))
+        .collect::<Vec<_>>();
+    let mut rest = rest.iter().rev();
+
+    loop {
+        let (mode, doc) = match stack.pop() {
+            Some(next) => next,
+            None => match rest.next() {
+                Some((_, mode, doc)) => (*mode, *doc),
+                None => return true,
+            },
+        };
+
+        match doc {
+            Doc::Text(text) => {
+                let line = text.split('\n').next().unwrap_or_default();
+                remaining -= line.chars().count() as isize;
+                if remaining < 0 {
+                    return false;
+                }
+
+                if line.len() < text.len() {
+                    return true;
+                }
+            }
+            Doc::Line | Doc::SoftLine if mode == Mode::Flat => {
+                if matches!(doc, Doc::Line) {
+                    remaining -= 1;
+                    if remaining < 0 {
+                        return false;
+                    }
+                }
+            }
+            Doc::Line | Doc::SoftLine | Doc::HardLine => return true,
+            Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (mode, doc))),
+            Doc::Indent(doc) => stack.push((mode, doc)),
+            Doc::Group(docs, broken) => {
+                let mode = if *broken { Mode::Break } else { mode };
+                stack.extend(docs.iter().rev().map(|doc| (mode, doc)));
+            }
+            Doc::IfBreak(text) => {
+                if mode == Mode::Break {
+                    remaining -= text.len() as isize;
+                }
+            }
+        }
+    }
+}
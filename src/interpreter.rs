@@ -1,59 +1,130 @@
 mod builtins;
 mod capture;
+mod config;
+mod convert;
+mod data;
 mod function;
+mod heap;
+mod interrupt;
+mod io;
 mod list;
+mod loader;
 mod native;
 mod object;
 mod rid;
+mod userdata;
 mod value;
+mod worker;
 
+pub use self::config::InterpreterConfig;
+pub use self::convert::{FromValue, IntoValue, TypedCallback};
+pub use self::data::ModuleHandler;
 pub use self::function::Function;
+pub use self::heap::{GcStats, HeapStats, HeapUsage};
+pub use self::interrupt::InterruptHandle;
+pub use self::io::CaptureBuffer;
 pub use self::list::List;
+pub use self::loader::{FileSystemLoader, MemoryLoader, ModuleLoader};
+pub use self::native::{
+    ExternalCallContext, ExternalProcedureCallback, ExternalProcedureClosure, NativeModule,
+};
 pub use self::object::Object;
+pub use self::userdata::{
+    UserData, UserDataClass, UserDataGetIndexCallback, UserDataMethod, UserDataMethodCallback,
+    UserDataSetIndexCallback,
+};
 pub use self::value::{Value, ValueType};
 
+use std::any::Any;
 use std::collections::HashMap;
+use std::io::{stdin, stdout, BufRead, BufReader, Write};
+
+use indexmap::IndexSet;
 
+use crate::ast::{Chunk, Stmt};
 use crate::bytecode::{
-    Bytecode, Environment, ExportLocation, Instruction, Module, Procedure, StackLocation,
-    VariableVariant,
+    Bytecode, Environment, ExportLocation, Instruction, Module, Procedure, ResolvedImports,
+    ResolvedModule, StackLocation, VariableVariant,
 };
 use crate::error::{RegisError, RegisErrorVariant};
 use crate::lexer::Symbol;
 use crate::parser::Parser;
-use crate::shared::{SharedImmutable, SharedMutable};
-use crate::source::{CanonicalPath, Location};
+use crate::shared::{MaybeSend, SharedImmutable, SharedMutable};
+use crate::source::{CanonicalPath, Location, ModuleKey};
 
 use self::capture::Capture;
 use self::function::ProcedureVariant;
-use self::native::{ExternalCallContext, ExternalProcedure, ExternalProcedureCallback};
+use self::heap::Heap;
+use self::io::{Input, Output};
+use self::native::ExternalProcedure;
 use self::rid::Rid;
 
-static DEBUG: bool = false;
-
 #[derive(Debug)]
 pub struct Interpreter {
     stack: Vec<StackValue>,
     frames: Vec<Frame>,
-    modules: HashMap<CanonicalPath, LoadedModule>,
+    modules: HashMap<ModuleKey, LoadedModule>,
+    loading: Vec<CanonicalPath>,
     environment: Environment,
     globals: Vec<Value>,
+    loader: Box<dyn ModuleLoader>,
+    config: InterpreterConfig,
+    interrupt: InterruptHandle,
+    output: Output,
+    input: Input,
+    search_paths: Vec<CanonicalPath>,
+    packages: HashMap<String, CanonicalPath>,
+    handlers: HashMap<String, ModuleHandler>,
+    // Kept so they can be registered again in workers.
+    #[cfg(feature = "sync")]
+    native_modules: Vec<(String, NativeModule)>,
     next_id: Rid,
+    heap: Heap,
+    instructions: usize,
+    allocations: usize,
 }
 
 #[allow(clippy::unnecessary_wraps)]
 impl Interpreter {
     pub fn new(main: CanonicalPath) -> Self {
+        Self::with_loader(main, FileSystemLoader::new())
+    }
+
+    pub fn with_loader<L: ModuleLoader + 'static>(main: CanonicalPath, loader: L) -> Self {
+        Self::with_config(main, loader, InterpreterConfig::default())
+    }
+
+    pub fn with_config<L: ModuleLoader + 'static>(
+        main: CanonicalPath,
+        loader: L,
+        config: InterpreterConfig,
+    ) -> Self {
         let mut result = Self {
             stack: Vec::new(),
             frames: vec![Frame::new(0, FrameVariant::Module(main.clone()))],
             modules: HashMap::new(),
+            loading: Vec::new(),
             environment: Environment::new(main),
             globals: Vec::new(),
+            loader: Box::new(loader),
+            config,
+            interrupt: InterruptHandle::new(),
+            output: Output::new(stdout()),
+            input: Input::new(BufReader::new(stdin())),
+            search_paths: Vec::new(),
+            packages: HashMap::new(),
+            handlers: HashMap::new(),
+            #[cfg(feature = "sync")]
+            native_modules: Vec::new(),
             next_id: Rid::new(),
+            heap: Heap::new(),
+            instructions: 0,
+            allocations: 0,
         };
 
         result.add_default_globals();
+        result.add_default_handlers();
+        result.reset_counters();
         result
     }
 
@@ -61,9 +132,99 @@ impl Interpreter {
         &self.environment
     }
 
+    pub fn loader(&self) -> &dyn ModuleLoader {
+        self.loader.as_ref()
+    }
+
+    pub fn set_loader<L: ModuleLoader + 'static>(&mut self, loader: L) {
+        self.loader = Box::new(loader);
+    }
+
+    pub fn output(&mut self) -> &mut dyn Write {
+        self.output.writer()
+    }
+
+    pub fn set_output<W: Write + MaybeSend + 'static>(&mut self, output: W) {
+        self.output = Output::new(output);
+    }
+
+    pub fn input(&mut self) -> &mut dyn BufRead {
+        self.input.reader()
+    }
+
+    pub fn set_input<R: BufRead + MaybeSend + 'static>(&mut self, input: R) {
+        self.input = Input::new(input);
+    }
+
+    pub fn config(&self) -> &InterpreterConfig {
+        &self.config
+    }
+
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.clone()
+    }
+
+    pub fn search_paths(&self) -> &[CanonicalPath] {
+        &self.search_paths
+    }
+
+    pub fn add_search_path(&mut self, path: CanonicalPath) {
+        self.search_paths.push(path);
+    }
+
+    pub fn add_package(&mut self, name: &str, path: CanonicalPath) {
+        self.packages.insert(name.into(), path);
+    }
+
+    pub fn add_module_handler(&mut self, extension: &str, handler: ModuleHandler) {
+        self.handlers.insert(extension.into(), handler);
+    }
+
+    pub fn instruction_count(&self) -> usize {
+        self.instructions
+    }
+
+    pub fn allocation_count(&self) -> usize {
+        self.allocations
+    }
+
+    pub fn reset_counters(&mut self) {
+        self.instructions = 0;
+        self.allocations = 0;
+    }
+
+    // Limits and interrupts apply to each run started by the host. Nothing is reset for imports or
+    // for calls made from native functions while a run is in progress.
+    fn start_run(&mut self) {
+        if self.frames.len() == 1 && self.loading.is_empty() {
+            self.reset_counters();
+            self.interrupt.reset();
+        }
+    }
+
+    pub fn gc(&mut self) -> usize {
+        self.heap.collect()
+    }
+
+    pub fn gc_stats(&self) -> GcStats {
+        self.heap.stats()
+    }
+
+    pub fn heap_stats(&mut self) -> HeapStats {
+        let stack = self.stack.iter().filter_map(|value| match value {
+            StackValue::Value(value) => Some(value),
+            StackValue::Capture(..) => None,
+        });
+        let modules = self.modules.values().map(LoadedModule::value);
+
+        self.heap
+            .measure(stack.chain(self.globals.iter()).chain(modules))
+    }
+
     pub fn generate_id(&mut self) -> Rid {
         let id = self.next_id;
         self.next_id = self.next_id.next();
+        self.allocations += 1;
         id
     }
 
@@ -82,7 +243,28 @@ impl Interpreter {
         arity: usize,
         callback: ExternalProcedureCallback,
     ) {
+        self.add_global_closure(name, arity, callback);
+    }
+
+    pub fn add_global_closure<F>(&mut self, name: String, arity: usize, callback: F)
+    where
+        F: Fn(&[Value], &mut ExternalCallContext) -> Result<Value, RegisError>
+            + MaybeSend
+            + 'static,
+    {
         let procedure = ExternalProcedure::new(SharedImmutable::new(name.clone()), arity, callback);
+        self.add_global_procedure(name, procedure);
+    }
+
+    pub fn add_global_typed_function<F, A>(&mut self, name: String, callback: F)
+    where
+        F: TypedCallback<A> + MaybeSend + 'static,
+    {
+        let procedure = ExternalProcedure::from_typed(SharedImmutable::new(name.clone()), callback);
+        self.add_global_procedure(name, procedure);
+    }
+
+    fn add_global_procedure(&mut self, name: String, procedure: ExternalProcedure) {
         let function = Value::Function(
             Function::new(
                 self.generate_id(),
@@ -91,43 +273,106 @@ impl Interpreter {
             .into(),
         );
 
+        let function = self.track(function);
         self.add_global(name, function);
     }
 
+    pub fn register_native_module(&mut self, name: &str, module: NativeModule) {
+        #[cfg(feature = "sync")]
+        self.native_modules.push((name.into(), module.clone()));
+
+        let exports = module.build(self);
+        let exports = self.track(Value::Object(exports.into()));
+        self.modules
+            .insert(ModuleKey::Native(name.into()), LoadedModule::data(exports));
+    }
+
+    pub fn module_exports(&self, key: &ModuleKey) -> Option<&SharedMutable<Object>> {
+        self.modules.get(key).and_then(LoadedModule::exports)
+    }
+
+    pub fn module_value(&self, key: &ModuleKey) -> Option<&Value> {
+        self.modules.get(key).map(LoadedModule::value)
+    }
+
+    pub fn module_paths(&self) -> impl Iterator<Item = &CanonicalPath> {
+        self.modules.keys().filter_map(|key| match key {
+            ModuleKey::Path(path) => Some(path),
+            ModuleKey::Native(..) => None,
+        })
+    }
+
     fn add_default_globals(&mut self) {
         self.add_global_function("@print".into(), 1, builtins::print);
         self.add_global_function("@println".into(), 1, builtins::println);
         self.add_global_function("@len".into(), 1, builtins::len);
         self.add_global_function("@import".into(), 1, builtins::import);
         self.add_global_function("@sleep".into(), 1, builtins::sleep);
+        self.add_global_function("@readline".into(), 0, builtins::readline);
+        self.add_global_function("@gc".into(), 0, builtins::gc);
+        self.add_global_function("@heap_stats".into(), 0, builtins::heap_stats);
+        self.add_global_function("@worker".into(), 1, builtins::worker);
+        self.add_global_function("@send".into(), 2, builtins::send);
+        self.add_global_function("@recv".into(), 1, builtins::recv);
+
+        // Workers replace this with the end of the channels that leads back to their parent.
+        self.add_global("@parent".into(), Value::Null);
+    }
+
+    // Registers a value with the garbage collector so it can be freed if it ends up in a reference
+    // cycle.
+    fn track(&mut self, value: Value) -> Value {
+        self.heap.track(&value);
+        value
+    }
+
+    fn add_default_handlers(&mut self) {
+        self.add_module_handler("json", data::json);
     }
 
     pub fn load_module(&mut self, path: &CanonicalPath) -> Result<(), RegisError> {
-        if self.modules.contains_key(&path) {
+        self.start_run();
+
+        if let Some(position) = self.loading.iter().position(|loading| loading == path) {
+            let mut chain = self.loading[position..]
+                .iter()
+                .map(|path| path.to_string())
+                .collect::<Vec<_>>();
+            chain.push(path.to_string());
+
+            return Err(RegisError::new(
+                None,
+                RegisErrorVariant::CircularImportError { chain },
+            ));
+        }
+
+        if self.modules.contains_key(&ModuleKey::Path(path.clone())) {
             return Ok(());
         }
 
-        if let Ok(source) = path.read() {
-            let ast = match Parser::new(&source).parse() {
-                Ok(ast) => ast,
-                Err(error) => {
-                    return Err(RegisError::new(
-                        Some(Location::new(Some(path.clone()), *error.span())),
-                        RegisErrorVariant::ParseError {
-                            message: error.to_string(),
-                        },
-                    ));
-                }
-            };
+        if let Some(source) = self.loader.load(path) {
+            // Files with an extension that has a registered handler are data rather than code. The
+            // handler converts them straight to a value.
+            let handler = path
+                .as_ref()
+                .extension()
+                .and_then(|extension| self.handlers.get(extension.to_str()?))
+                .copied();
+
+            if let Some(handler) = handler {
+                let value = handler(&source, path, self)?;
+                self.modules
+                    .insert(ModuleKey::Path(path.clone()), LoadedModule::data(value));
+                return Ok(());
+            }
 
-            let module = Module::build(
-                path.clone(),
-                &ast,
-                self.environment().for_module(path.clone()),
-            )
-            .into();
+            self.loading.push(path.clone());
+            let result = self
+                .build_module(path, &source, false)
+                .and_then(|module| self.run_module(module));
+            self.loading.pop();
 
-            self.run_module(module)
+            result.map(|_| ())
         } else {
             Err(RegisError::new(
                 None,
@@ -138,13 +383,281 @@ impl Interpreter {
         }
     }
 
-    fn run_module(&mut self, module: SharedImmutable<Module>) -> Result<(), RegisError> {
+    pub fn reload_module(&mut self, path: &CanonicalPath) -> Result<(), RegisError> {
+        let key = ModuleKey::Path(path.clone());
+        let previous = match self.modules.remove(&key) {
+            Some(previous) => previous,
+            None => return self.load_module(path),
+        };
+
+        // Load the module again as if it had never been imported. Keep the previous version if
+        // that fails.
+        if let Err(error) = self.load_module(path) {
+            self.modules.insert(key, previous);
+            return Err(error);
+        }
+
+        // Move the new exports into the previous exports object so anything holding on to it sees
+        // the new values.
+        let loaded = self.modules.get_mut(&key).unwrap();
+        if let (Value::Object(exports), Some(previous_exports)) =
+            (&loaded.value, previous.exports())
+        {
+            let mut previous_exports = previous_exports.borrow_mut();
+            previous_exports.clear();
+            for (name, value) in exports.borrow().iter() {
+                previous_exports.set(name.clone(), value.clone());
+            }
+            drop(previous_exports);
+
+            loaded.value = previous.value;
+        }
+
+        Ok(())
+    }
+
+    pub fn eval_source(&mut self, name: &str, source: &str) -> Result<Value, RegisError> {
+        self.start_run();
+
+        let path = CanonicalPath::from_virtual(name);
+        self.loading.push(path.clone());
+        let result = self
+            .build_module(&path, source, true)
+            .and_then(|module| self.run_module(module));
+        self.loading.pop();
+
+        // Return the value of the last expression in the source if there is one. Otherwise return
+        // the module's exports.
+        match result? {
+            Some(result) => Ok(result),
+            None => Ok(Value::Object(
+                self.module_exports(&ModuleKey::Path(path)).unwrap().clone(),
+            )),
+        }
+    }
+
+    pub fn create_user_data<T: Any + MaybeSend>(
+        &mut self,
+        class: &SharedImmutable<UserDataClass>,
+        value: T,
+    ) -> Value {
+        Value::UserData(UserData::new(self.generate_id(), class.clone(), value).into())
+    }
+
+    pub fn call(&mut self, target: &Value, arguments: &[Value]) -> Result<Value, RegisError> {
+        self.start_run();
+
+        // Store the current state of the stack so it can be restored if the call fails.
+        let frame_count = self.frames.len();
+        let position = self.top();
+
+        for argument in arguments {
+            self.push_value(argument.clone());
+        }
+
+        match self.run_call(target, arguments.len()) {
+            Ok(()) => Ok(self.pop_value()),
+            Err(error) => {
+                self.frames.truncate(frame_count);
+                self.pop_values_to(position);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn parse_module(&self, path: &CanonicalPath) -> Result<Chunk, RegisError> {
+        match self.loader.load(path) {
+            Some(source) => parse(path, &source),
+            None => Err(RegisError::new(
+                None,
+                RegisErrorVariant::ModuleDoesNotExistError {
+                    path: path.to_string(),
+                },
+            )),
+        }
+    }
+
+    // Builds a module without running it or any module it imports. Building only needs to know
+    // what imported modules export, which is read from their source.
+    pub fn compile_module(
+        &mut self,
+        path: &CanonicalPath,
+    ) -> Result<SharedImmutable<Module>, RegisError> {
+        match self.loader.load(path) {
+            Some(source) => {
+                let ast = parse(path, &source)?;
+                let imports = self.resolve_imports(path, &ast, false)?;
+                let environment = self.environment().for_module(path.clone());
+                Ok(Module::build(path.clone(), &ast, environment, &imports)?.into())
+            }
+            None => Err(RegisError::new(
+                None,
+                RegisErrorVariant::ModuleDoesNotExistError {
+                    path: path.to_string(),
+                },
+            )),
+        }
+    }
+
+    fn build_module(
+        &mut self,
+        path: &CanonicalPath,
+        source: &str,
+        has_result: bool,
+    ) -> Result<SharedImmutable<Module>, RegisError> {
+        let ast = parse(path, source)?;
+        let imports = self.resolve_imports(path, &ast, true)?;
+        let environment = self.environment().for_module(path.clone());
+        let module = if has_result {
+            Module::build_with_result(path.clone(), &ast, environment, &imports)?
+        } else {
+            Module::build(path.clone(), &ast, environment, &imports)?
+        };
+
+        Ok(module.into())
+    }
+
+    fn resolve_imports(
+        &mut self,
+        path: &CanonicalPath,
+        Chunk { stmts, .. }: &Chunk,
+        load: bool,
+    ) -> Result<ResolvedImports, RegisError> {
+        // Load every module imported by a static import statement before the importing module is
+        // built, so the builder knows what each of them exports. When only compiling, their
+        // exports are found without loading them.
+        let mut imports = ResolvedImports::new();
+        for stmt in stmts {
+            let specifier = match stmt {
+                Stmt::Import(stmt) => &stmt.path,
+                Stmt::ExportFrom(stmt) => &stmt.path,
+                _ => continue,
+            };
+
+            if imports.contains_key(&specifier.value) {
+                continue;
+            }
+
+            let module = if load {
+                self.import_module(&specifier.value, &path.parent())?
+                    .map(|module| ResolvedModule {
+                        exports: self.modules.get(&module).unwrap().export_names(),
+                        module,
+                    })
+            } else {
+                self.inspect_module(&specifier.value, &path.parent())?
+            };
+
+            let module = module.ok_or_else(|| {
+                RegisError::new(
+                    Some(Location::new(Some(path.clone()), *specifier.info.span())),
+                    RegisErrorVariant::ModuleDoesNotExistError {
+                        path: specifier.value.to_string(),
+                    },
+                )
+            })?;
+            imports.insert(specifier.value.clone(), module);
+        }
+
+        Ok(imports)
+    }
+
+    fn import_module(
+        &mut self,
+        specifier: &str,
+        root: &CanonicalPath,
+    ) -> Result<Option<ModuleKey>, RegisError> {
+        // Native modules registered by the host take precedence over modules on the loader.
+        let native = ModuleKey::Native(specifier.into());
+        if self.modules.contains_key(&native) {
+            return Ok(Some(native));
+        }
+
+        if let Some(path) = self.resolve_module(specifier, root)? {
+            self.load_module(&path)?;
+            Ok(Some(ModuleKey::Path(path)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Finds what a module exports without running it. Modules that are already loaded are used as
+    // they are, and data modules are loaded since that doesn't run any code.
+    fn inspect_module(
+        &mut self,
+        specifier: &str,
+        root: &CanonicalPath,
+    ) -> Result<Option<ResolvedModule>, RegisError> {
+        let native = ModuleKey::Native(specifier.into());
+        if let Some(loaded) = self.modules.get(&native) {
+            return Ok(Some(ResolvedModule {
+                exports: loaded.export_names(),
+                module: native,
+            }));
+        }
+
+        let path = match self.resolve_module(specifier, root)? {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+
+        let is_data = path
+            .as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| self.handlers.contains_key(extension));
+        if is_data {
+            self.load_module(&path)?;
+        }
+
+        let module = ModuleKey::Path(path.clone());
+        let exports = match self.modules.get(&module) {
+            Some(loaded) => loaded.export_names(),
+            None => export_names(&self.parse_module(&path)?),
+        };
+
+        Ok(Some(ResolvedModule { module, exports }))
+    }
+
+    fn resolve_module(
+        &self,
+        specifier: &str,
+        root: &CanonicalPath,
+    ) -> Result<Option<CanonicalPath>, RegisError> {
+        // Paths starting with the name of a package are resolved from the package's directory.
+        let package = specifier
+            .split('/')
+            .next()
+            .and_then(|name| self.packages.get(name))
+            .map(|directory| {
+                let rest = &specifier[specifier.find('/').unwrap_or(specifier.len())..];
+                format!("{}{}", directory, rest)
+            });
+
+        if self.loader.uses_filesystem() {
+            self.config.check_filesystem()?;
+        }
+
+        let specifier = package.as_deref().unwrap_or(specifier);
+        if let Some(path) = self.loader.resolve(specifier, root, &self.search_paths) {
+            self.config.check_import(&path)?;
+            Ok(Some(path))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn run_module(&mut self, module: SharedImmutable<Module>) -> Result<Option<Value>, RegisError> {
         // Add the module to the set of loaded modules.
-        let loaded = LoadedModule::new(self.generate_id(), module.clone());
-        self.modules.insert(module.path().clone(), loaded);
+        let key = ModuleKey::Path(module.path().clone());
+        let exports = Object::new(self.generate_id());
+        let exports = self.track(Value::Object(exports.into()));
+        self.modules
+            .insert(key.clone(), LoadedModule::new(module.clone(), exports));
 
         // Push a new module frame onto the stack. Store the position we return to to after its
         // evalutated.
+        let frame_count = self.frames.len();
         self.frames.push(Frame::new(
             self.top(),
             FrameVariant::Module(module.path().clone()),
@@ -155,8 +668,22 @@ impl Interpreter {
             self.push_value(Value::Null);
         }
 
-        // Run the bytecode instructions.
-        self.run_bytecode(module.bytecode(), module.environment())?;
+        // Run the bytecode instructions. If they fail, unwind the module's frames and discard it
+        // so it isn't treated as loaded by later imports.
+        if let Err(error) = self.run_bytecode(module.bytecode(), module.environment()) {
+            let position = self.frames[frame_count].position();
+            self.frames.truncate(frame_count);
+            self.pop_values_to(position);
+            self.modules.remove(&key);
+            return Err(error);
+        }
+
+        // If the module produces a result, pop it off the top of the stack.
+        let result = if module.has_result() {
+            Some(self.pop_value())
+        } else {
+            None
+        };
 
         // Pop the module frame.
         let frame = self.frames.pop().unwrap();
@@ -164,7 +691,7 @@ impl Interpreter {
         // Discard all local variables allocated for the module.
         self.pop_values_to(frame.position());
 
-        Ok(())
+        Ok(result)
     }
 
     fn run_function(
@@ -227,31 +754,112 @@ impl Interpreter {
         Ok(())
     }
 
+    fn run_call(&mut self, target: &Value, argument_count: usize) -> Result<(), RegisError> {
+        match target {
+            Value::Function(function) => self.run_function(function, argument_count),
+            Value::UserData(data) => self.run_user_data_call(data, argument_count),
+            _ => Err(not_callable_error(target)),
+        }
+    }
+
+    fn run_user_data_call(
+        &mut self,
+        data: &SharedImmutable<UserData>,
+        argument_count: usize,
+    ) -> Result<(), RegisError> {
+        let class = data.class().clone();
+        let (arity, callback) = match class.call() {
+            Some(call) => call,
+            None => return Err(not_callable_error(&Value::UserData(data.clone()))),
+        };
+
+        let arguments = self.pop_arguments(class.name(), arity, argument_count)?;
+        let result = callback(
+            data,
+            &arguments,
+            &mut ExternalCallContext { interpreter: self },
+        )?;
+
+        self.push_value(result);
+        Ok(())
+    }
+
+    fn get_user_data_index(
+        &mut self,
+        data: &SharedImmutable<UserData>,
+        index: &Value,
+    ) -> Result<Value, RegisError> {
+        // Methods are bound to the user data they're accessed from, so they can be called like
+        // normal functions.
+        if let Value::String(name) = index {
+            if let Some(method) = data.class().method(name) {
+                let method = method.clone();
+                let this = data.clone();
+                let procedure = ExternalProcedure::new(
+                    name.clone(),
+                    method.arity(),
+                    move |arguments, context| method.call(&this, arguments, context),
+                );
+
+                let function = Value::Function(
+                    Function::bound(
+                        self.generate_id(),
+                        ProcedureVariant::External(procedure.into()),
+                        data.id(),
+                        name.clone(),
+                    )
+                    .into(),
+                );
+
+                return Ok(self.track(function));
+            }
+        }
+
+        match data.class().get_index() {
+            Some(callback) => callback(data, index, &mut ExternalCallContext { interpreter: self }),
+            None => Err(RegisError::new(
+                None,
+                RegisErrorVariant::TypeError {
+                    message: format!("Cannot get index of type '{}'.", data.type_of()),
+                },
+            )),
+        }
+    }
+
     fn call_external_procedure(
         &mut self,
         procedure: &ExternalProcedure,
         argument_count: usize,
     ) -> Result<Value, RegisError> {
-        if argument_count < procedure.arity() {
-            let name = procedure.name();
+        let arguments = self.pop_arguments(procedure.name(), procedure.arity(), argument_count)?;
+        procedure.call(&arguments, &mut ExternalCallContext { interpreter: self })
+    }
+
+    fn pop_arguments(
+        &mut self,
+        name: &SharedImmutable<String>,
+        arity: usize,
+        argument_count: usize,
+    ) -> Result<Vec<Value>, RegisError> {
+        if argument_count < arity {
             return Err(RegisError::new(
                 None,
                 RegisErrorVariant::ArgumentCountError {
                     function_name: Some(name.clone_inner()),
-                    required: procedure.arity(),
+                    required: arity,
                     actual: argument_count,
                 },
             ));
         }
-        let mut arguments = Vec::with_capacity(procedure.arity());
+
+        let mut arguments = Vec::with_capacity(argument_count);
         for _ in 0..argument_count {
             arguments.push(self.pop_value());
         }
 
-        procedure.call(
-            &arguments[..argument_count],
-            &mut ExternalCallContext { interpreter: self },
-        )
+        // Arguments are popped off the stack last to first, so put them back in order.
+        arguments.reverse();
+        Ok(arguments)
     }
 
     fn run_bytecode(
@@ -270,6 +878,10 @@ impl Interpreter {
             ptr.take();
 
             for (i, instruction) in instructions[start..].iter().enumerate() {
+                if let Err(error) = self.check_execution() {
+                    return Err(Self::locate_error(error, bytecode, environment, start + i));
+                }
+
                 let result = match instruction {
                     Instruction::Blank => Ok(()),
                     Instruction::Pop => self.instruction_pop(),
@@ -308,6 +920,7 @@ impl Interpreter {
                     }
                     Instruction::PushExport(location) => self.instruction_push_export(location),
                     Instruction::AssignExport(location) => self.instruction_assign_export(location),
+                    Instruction::PushModule(module) => self.instruction_push_module(module),
                     Instruction::PushGlobal(address) => self.instruction_push_global(*address),
                     Instruction::CreateList(size) => self.instruction_create_list(*size),
                     Instruction::CreateObject(size) => self.instruction_create_object(*size),
@@ -337,15 +950,7 @@ impl Interpreter {
                 };
 
                 if let Err(error) = result {
-                    let location = error.location().clone().unwrap_or_else(|| {
-                        Location::new(
-                            Some(environment.path().clone()),
-                            bytecode.spans()[start + i],
-                        )
-                    });
-                    let variant = error.variant().clone();
-
-                    return Err(RegisError::new(Some(location), variant));
+                    return Err(Self::locate_error(error, bytecode, environment, start + i));
                 }
             }
         }
@@ -353,6 +958,63 @@ impl Interpreter {
         Ok(())
     }
 
+    fn check_execution(&mut self) -> Result<(), RegisError> {
+        self.interrupt.check()?;
+
+        if self.heap.should_collect() {
+            self.heap.collect();
+        }
+
+        self.instructions += 1;
+
+        let max_allocations = self.config.max_allocations();
+        let max_heap_size = self.config.max_heap_size();
+        let heap_exceeded = self.heap.exceeds(max_allocations, max_heap_size);
+        let heap_count = self.heap.count();
+
+        let config = &self.config;
+        let variant = if let Some(limit) = config
+            .max_instructions()
+            .filter(|limit| self.instructions > *limit)
+        {
+            RegisErrorVariant::InstructionLimitError { limit }
+        } else if let Some(limit) = config
+            .max_call_depth()
+            .filter(|limit| self.frames.len() > *limit)
+        {
+            RegisErrorVariant::CallDepthLimitError { limit }
+        } else if let Some(limit) = config
+            .max_stack_size()
+            .filter(|limit| self.stack.len() > *limit)
+        {
+            RegisErrorVariant::StackLimitError { limit }
+        } else if let Some(limit) =
+            max_allocations.filter(|limit| heap_exceeded && heap_count > *limit)
+        {
+            RegisErrorVariant::HeapLimitError { limit }
+        } else if let Some(limit) = max_heap_size.filter(|_| heap_exceeded) {
+            RegisErrorVariant::HeapSizeLimitError { limit }
+        } else {
+            return Ok(());
+        };
+
+        Err(RegisError::new(None, variant))
+    }
+
+    fn locate_error(
+        error: RegisError,
+        bytecode: &Bytecode,
+        environment: &Environment,
+        index: usize,
+    ) -> RegisError {
+        let location = error.location().clone().unwrap_or_else(|| {
+            Location::new(Some(environment.path().clone()), bytecode.spans()[index])
+        });
+        let variant = error.variant().clone();
+
+        RegisError::new(Some(location), variant)
+    }
+
     fn top(&self) -> usize {
         self.stack.len()
     }
@@ -386,6 +1048,7 @@ impl Interpreter {
         match self.stack[position].clone() {
             StackValue::Value(value) => {
                 let capture = SharedMutable::new(Capture::new(value));
+                self.heap.track_capture(&capture);
                 self.stack[position] = StackValue::Capture(capture.clone());
                 capture
             }
@@ -398,44 +1061,18 @@ impl Interpreter {
     }
 
     fn push_stack_value(&mut self, value: StackValue) {
-        if DEBUG {
-            println!("DEBUG:   Push -> {:#?}", value);
-        }
-
         self.stack.push(value);
-
-        if DEBUG {
-            println!("DEBUG:   Size -> {:#?}", self.stack.len());
-        }
     }
 
     fn push_stack_values(&mut self, values: &[StackValue]) {
-        if DEBUG {
-            for value in values {
-                println!("DEBUG:   Push -> {:#?}", value);
-            }
-        }
-
         self.stack.extend_from_slice(values);
-
-        if DEBUG {
-            println!("DEBUG:   Size -> {:#?}", self.stack.len());
-        }
     }
 
     fn pop_value(&mut self) -> Value {
-        let result = self
-            .stack
+        self.stack
             .pop()
-            .unwrap_or_else(|| panic!("No values exist to be popped off the stack."));
-
-        if DEBUG {
-            println!("DEBUG:   Pop  -> {:#?}", result);
-            println!("DEBUG:   TOS  -> {:#?}", self.stack.last());
-            println!("DEBUG:   Size -> {:#?}", self.stack.len());
-        }
-
-        result.get()
+            .unwrap_or_else(|| panic!("No values exist to be popped off the stack."))
+            .get()
     }
 
     fn pop_values(&mut self, count: usize) {
@@ -443,31 +1080,14 @@ impl Interpreter {
     }
 
     fn pop_values_to(&mut self, position: usize) {
-        if DEBUG {
-            for value in self.stack.iter().rev().take(self.top() - position) {
-                println!("DEBUG:   Pop -> {:#?}", value);
-            }
-        }
-
         self.stack.truncate(position);
-
-        if DEBUG {
-            println!("DEBUG:   Size -> {:#?}", self.stack.len());
-        }
     }
 
     fn top_value(&self) -> Value {
-        let result = self
-            .stack
+        self.stack
             .last()
-            .unwrap_or_else(|| panic!("No value at top of stack."));
-
-        if DEBUG {
-            println!("DEBUG:   TOS  -> {:?}", self.stack.last());
-            println!("DEBUG:   Size -> {:?}", self.stack.len());
-        }
-
-        result.get()
+            .unwrap_or_else(|| panic!("No value at top of stack."))
+            .get()
     }
 
     fn get_variable(&self, address: usize) -> Value {
@@ -554,20 +1174,11 @@ impl Interpreter {
 
     fn instruction_push_export(
         &mut self,
-        ExportLocation {
-            path: module,
-            export,
-        }: &ExportLocation,
+        ExportLocation { module, export }: &ExportLocation,
     ) -> Result<(), RegisError> {
         let value = self
-            .modules
-            .get(module)
-            .map(|module| {
-                module
-                    .exports()
-                    .borrow()
-                    .get(&Value::String(export.clone()))
-            })
+            .module_exports(module)
+            .map(|exports| exports.borrow().get(&Value::String(export.clone())))
             .unwrap_or_else(|| {
                 panic!(
                     "Attempted to push export variable {} which does not exist.",
@@ -581,17 +1192,12 @@ impl Interpreter {
 
     fn instruction_assign_export(
         &mut self,
-        ExportLocation {
-            path: module,
-            export,
-        }: &ExportLocation,
+        ExportLocation { module, export }: &ExportLocation,
     ) -> Result<(), RegisError> {
         let value = self.pop_value();
-        self.modules
-            .get(module)
-            .map(|module| {
-                module
-                    .exports()
+        self.module_exports(module)
+            .map(|exports| {
+                exports
                     .borrow_mut()
                     .set(Value::String(export.clone()), value);
             })
@@ -604,6 +1210,18 @@ impl Interpreter {
         Ok(())
     }
 
+    fn instruction_push_module(&mut self, module: &ModuleKey) -> Result<(), RegisError> {
+        let value = self.module_value(module).cloned().unwrap_or_else(|| {
+            panic!(
+                "Attempted to push module {} which has not been loaded.",
+                module
+            )
+        });
+
+        self.push_value(value);
+        Ok(())
+    }
+
     fn instruction_push_global(&mut self, address: usize) -> Result<(), RegisError> {
         self.push_value(self.globals[address].clone());
         Ok(())
@@ -616,7 +1234,8 @@ impl Interpreter {
             list.push(self.pop_value());
         }
 
-        self.push_value(Value::List(list.into()));
+        let list = self.track(Value::List(list.into()));
+        self.push_value(list);
         Ok(())
     }
 
@@ -629,7 +1248,8 @@ impl Interpreter {
             object.set(value.clone(), key.clone());
         }
 
-        self.push_value(Value::Object(object.into()));
+        let object = self.track(Value::Object(object.into()));
+        self.push_value(object);
         Ok(())
     }
 
@@ -637,18 +1257,27 @@ impl Interpreter {
         &mut self,
         procedure: SharedImmutable<Procedure>,
     ) -> Result<(), RegisError> {
-        let init = procedure
-            .environment()
-            .variables()
-            .iter()
-            .map(|variable| match &variable.variant {
-                VariableVariant::Local => StackValue::Value(Value::Null),
-                VariableVariant::Capture { location } => StackValue::Capture(
-                    self.capture_value(self.get_variable_position_from_stack_location(location)),
-                ),
-            })
-            .collect::<Vec<_>>()
-            .into_boxed_slice();
+        let init =
+            procedure
+                .environment()
+                .variables()
+                .iter()
+                .map(|variable| match &variable.variant {
+                    VariableVariant::Local => StackValue::Value(Value::Null),
+                    VariableVariant::Capture { location } => {
+                        // Capture locations are relative to the new function, whose frame doesn't
+                        // exist yet, so they're one frame closer to the current one.
+                        let location = StackLocation {
+                            ascend: location.ascend - 1,
+                            address: location.address,
+                        };
+                        StackValue::Capture(self.capture_value(
+                            self.get_variable_position_from_stack_location(&location),
+                        ))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .into_boxed_slice();
 
         let function = Value::Function(
             Function::with_init(
@@ -659,25 +1288,14 @@ impl Interpreter {
             .into(),
         );
 
-        self.push_value(function);
-        Ok(())
-    }
-
-    fn instruction_call(&mut self, argument_count: usize) -> Result<(), RegisError> {
-        let target = self.pop_value();
-        let function = match target {
-            Value::Function(function) => function,
-            _ => {
-                return Err(RegisError::new(
-                    None,
-                    RegisErrorVariant::TypeError {
-                        message: format!("Type '{}' is not callable.", target.type_of()),
-                    },
-                ));
-            }
-        };
+        let function = self.track(function);
+        self.push_value(function);
+        Ok(())
+    }
 
-        self.run_function(&function, argument_count)
+    fn instruction_call(&mut self, argument_count: usize) -> Result<(), RegisError> {
+        let target = self.pop_value();
+        self.run_call(&target, argument_count)
     }
 
     fn run_errorable_unary_operation<O: Fn(&mut Self, Value) -> Result<Value, RegisError>>(
@@ -750,17 +1368,20 @@ impl Interpreter {
                 (Value::Float(left), Value::Float(right)) => Value::Float(left + right),
                 (Value::Float(left), Value::Int(right)) => Value::Float(left + right as f64),
                 (Value::List(left), Value::List(right)) => {
-                    Value::List(left.borrow().concat(&right.borrow(), this.generate_id()))
+                    let id = this.generate_id();
+                    this.track(Value::List(left.borrow().concat(&right.borrow(), id)))
                 }
                 (Value::Object(left), Value::Object(right)) => {
-                    Value::Object(left.borrow().concat(&right.borrow(), this.generate_id()))
-                }
-                (Value::String(left), right) => {
-                    Value::String(format!("{}{}", left, right.to_string()).into())
-                }
-                (left, Value::String(right)) => {
-                    Value::String(format!("{}{}", left.to_string(), right).into())
+                    let id = this.generate_id();
+                    this.track(Value::Object(left.borrow().concat(&right.borrow(), id)))
                 }
+                // Strings built by concatenation are tracked so they count towards the heap limits.
+                (Value::String(left), right) => this.track(Value::String(
+                    format!("{}{}", left, right.to_string()).into(),
+                )),
+                (left, Value::String(right)) => this.track(Value::String(
+                    format!("{}{}", left.to_string(), right).into(),
+                )),
                 (left, right) => {
                     return Err(binary_operation_error(Symbol::Add.text(), left, right))
                 }
@@ -967,6 +1588,7 @@ impl Interpreter {
             }
             Value::List(list) => list.borrow().get(&index)?,
             Value::Object(object) => object.borrow().get(&index),
+            Value::UserData(data) => self.get_user_data_index(&data, &index)?,
             _ => {
                 return Err(RegisError::new(
                     None,
@@ -989,6 +1611,22 @@ impl Interpreter {
         match target {
             Value::List(list) => list.borrow_mut().set(index, value)?,
             Value::Object(object) => object.borrow_mut().set(index, value),
+            Value::UserData(data) => match data.class().set_index() {
+                Some(callback) => callback(
+                    &data,
+                    index,
+                    value,
+                    &mut ExternalCallContext { interpreter: self },
+                )?,
+                None => {
+                    return Err(RegisError::new(
+                        None,
+                        RegisErrorVariant::TypeError {
+                            message: format!("Cannot set index of type '{}'.", data.type_of()),
+                        },
+                    ));
+                }
+            },
             _ => {
                 return Err(RegisError::new(
                     None,
@@ -1046,25 +1684,95 @@ impl Frame {
 
 #[derive(Debug)]
 struct LoadedModule {
-    module: SharedImmutable<Module>,
-    exports: SharedMutable<Object>,
+    module: Option<SharedImmutable<Module>>,
+    value: Value,
 }
 
 impl LoadedModule {
-    pub fn new(id: Rid, module: SharedImmutable<Module>) -> Self {
+    pub fn new(module: SharedImmutable<Module>, exports: Value) -> Self {
+        Self {
+            module: Some(module),
+            value: exports,
+        }
+    }
+
+    pub fn data(value: Value) -> Self {
         Self {
-            module,
-            exports: Object::new(id).into(),
+            module: None,
+            value,
+        }
+    }
+
+    pub fn export_names(&self) -> IndexSet<SharedImmutable<String>> {
+        match (&self.module, &self.value) {
+            (Some(module), _) => module.environment().exports().clone(),
+            (None, Value::Object(exports)) => exports
+                .borrow()
+                .iter()
+                .filter_map(|(key, _)| match key {
+                    Value::String(key) => Some(key.clone()),
+                    _ => None,
+                })
+                .collect(),
+            (None, _) => IndexSet::new(),
         }
     }
 
-    // pub fn module(&self) -> &Module {
-    //     &self.module
-    // }
+    pub fn exports(&self) -> Option<&SharedMutable<Object>> {
+        match &self.value {
+            Value::Object(exports) => Some(exports),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
 
-    pub fn exports(&self) -> &SharedMutable<Object> {
-        &self.exports
+// The names a module exports, read from its syntax tree.
+fn export_names(Chunk { stmts, .. }: &Chunk) -> IndexSet<SharedImmutable<String>> {
+    let mut names = IndexSet::new();
+    for stmt in stmts {
+        match stmt {
+            Stmt::VariableDeclaration(stmt) if stmt.is_exported => {
+                names.insert(stmt.name.text.clone());
+            }
+            Stmt::FunctionDeclaration(stmt) if stmt.is_exported => {
+                names.extend(stmt.function.name.iter().map(|name| name.text.clone()));
+            }
+            Stmt::ExportFrom(stmt) => {
+                names.extend(
+                    stmt.specifiers
+                        .iter()
+                        .map(|specifier| specifier.local_name().text.clone()),
+                );
+            }
+            _ => {}
+        }
     }
+
+    names
+}
+
+fn parse(path: &CanonicalPath, source: &str) -> Result<Chunk, RegisError> {
+    Parser::new(source).parse().map_err(|error| {
+        RegisError::new(
+            Some(Location::new(Some(path.clone()), *error.span())),
+            RegisErrorVariant::ParseError {
+                message: error.to_string(),
+            },
+        )
+    })
+}
+
+fn not_callable_error(target: &Value) -> RegisError {
+    RegisError::new(
+        None,
+        RegisErrorVariant::TypeError {
+            message: format!("Type '{}' is not callable.", target.type_of()),
+        },
+    )
 }
 
 fn unary_operation_error(operator: &'static str, right: Value) -> RegisError {
@@ -1087,3 +1795,1105 @@ fn binary_operation_error(operator: &'static str, left: Value, right: Value) ->
         },
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    fn interpreter() -> Interpreter {
+        Interpreter::new(CanonicalPath::from_virtual("main"))
+    }
+
+    #[test]
+    fn eval_source_result() {
+        let mut interpreter = interpreter();
+        assert_eq!(
+            interpreter.eval_source("test", "1 + 2;").unwrap(),
+            Value::Int(3)
+        );
+        assert_eq!(
+            interpreter
+                .eval_source("test", "fn add(a, b) => a + b; add(2, 3);")
+                .unwrap(),
+            Value::Int(5)
+        );
+    }
+
+    #[test]
+    fn eval_source_exports() {
+        let mut interpreter = interpreter();
+        let exports = interpreter
+            .eval_source("test", "export let a = 1; export fn b() { return 2; }")
+            .unwrap();
+
+        match exports {
+            Value::Object(exports) => {
+                let exports = exports.borrow();
+                assert_eq!(exports.len(), 2);
+                assert_eq!(exports.get(&Value::String("a".into())), Value::Int(1));
+            }
+            other => panic!("Expected exports object. Got '{}'.", other.type_of()),
+        }
+    }
+
+    #[test]
+    fn call() {
+        let mut interpreter = interpreter();
+        let sub = interpreter
+            .eval_source("test", "fn sub(a, b) => a - b; sub;")
+            .unwrap();
+
+        assert_eq!(
+            interpreter
+                .call(&sub, &[Value::Int(5), Value::Int(3)])
+                .unwrap(),
+            Value::Int(2)
+        );
+        assert!(interpreter.call(&Value::Null, &[]).is_err());
+    }
+
+    #[test]
+    fn call_from_external_function() {
+        fn apply(
+            arguments: &[Value],
+            context: &mut ExternalCallContext,
+        ) -> Result<Value, RegisError> {
+            context.call(&arguments[1], &[arguments[0].clone()])
+        }
+
+        let mut interpreter = interpreter();
+        interpreter.add_global_function("@apply".into(), 2, apply);
+        assert_eq!(
+            interpreter
+                .eval_source("test", "@apply(10, fn(value) => value * 2);")
+                .unwrap(),
+            Value::Int(20)
+        );
+    }
+
+    #[test]
+    fn global_closure() {
+        use std::sync::atomic::{AtomicI64, Ordering};
+        use std::sync::Arc;
+
+        let count = Arc::new(AtomicI64::new(0));
+        let mut interpreter = interpreter();
+        {
+            let count = count.clone();
+            interpreter.add_global_closure("@count".into(), 0, move |_, _| {
+                Ok(Value::Int(count.fetch_add(1, Ordering::SeqCst) + 1))
+            });
+        }
+
+        assert_eq!(
+            interpreter
+                .eval_source("test", "@count(); @count(); @count();")
+                .unwrap(),
+            Value::Int(3)
+        );
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn typed_function() {
+        let mut interpreter = interpreter();
+        interpreter.add_global_typed_function("@repeat".into(), |text: String, count: i64| {
+            Ok(text.repeat(count as usize))
+        });
+        interpreter.add_global_typed_function("@sum".into(), |values: Vec<f64>| {
+            Ok(values.iter().sum::<f64>())
+        });
+
+        assert_eq!(
+            interpreter
+                .eval_source("test", "@repeat(\"ab\", 3);")
+                .unwrap(),
+            Value::String("ababab".into())
+        );
+        assert_eq!(
+            interpreter
+                .eval_source("test", "@sum([1, 2.5, 3]);")
+                .unwrap(),
+            Value::Float(6.5)
+        );
+
+        let error = interpreter
+            .eval_source("test", "@repeat(\"ab\", \"3\");")
+            .unwrap_err();
+        match error.variant() {
+            RegisErrorVariant::TypeError { message } => assert_eq!(
+                message,
+                "Argument 2 passed to @repeat() must be of type 'int'. Got 'string'."
+            ),
+            other => panic!("Expected type error. Got {:?}.", other),
+        }
+    }
+
+    #[test]
+    fn convert_values() {
+        use std::collections::HashMap;
+
+        let mut interpreter = interpreter();
+        let value = vec![(1i64, Some(String::from("a"))), (2, None)].into_value(&mut interpreter);
+        assert_eq!(
+            Vec::<(i64, Option<String>)>::from_value(&value),
+            Some(vec![(1, Some("a".into())), (2, None)])
+        );
+        assert_eq!(Vec::<(i64, String)>::from_value(&value), None);
+
+        let mut map = HashMap::new();
+        map.insert(String::from("a"), true);
+        let value = map.clone().into_value(&mut interpreter);
+        assert_eq!(HashMap::<String, bool>::from_value(&value), Some(map));
+    }
+
+    #[test]
+    fn user_data() {
+        struct Counter {
+            count: i64,
+        }
+
+        let class = SharedImmutable::new(
+            UserDataClass::new("Counter")
+                .with_method("increment", 0, |this, _, _| {
+                    let mut counter = this.borrow_mut::<Counter>().unwrap();
+                    counter.count += 1;
+                    Ok(Value::Int(counter.count))
+                })
+                .with_get_index(|this, index, _| match index {
+                    Value::String(name) if **name == "count" => {
+                        Ok(Value::Int(this.borrow::<Counter>().unwrap().count))
+                    }
+                    _ => Ok(Value::Null),
+                })
+                .with_call(1, |this, arguments, _| {
+                    let mut counter = this.borrow_mut::<Counter>().unwrap();
+                    counter.count += arguments[0].to_boolean() as i64;
+                    Ok(Value::Int(counter.count))
+                }),
+        );
+
+        let mut interpreter = interpreter();
+        let counter = interpreter.create_user_data(&class, Counter { count: 0 });
+        interpreter.add_global("counter".into(), counter.clone());
+
+        assert_eq!(counter.type_of().to_string(), "Counter");
+        assert_eq!(counter.to_string(), "<userdata:Counter>");
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "test",
+                    "counter.increment(); counter.increment(); counter(true); counter.count;"
+                )
+                .unwrap(),
+            Value::Int(3)
+        );
+        assert!(interpreter
+            .eval_source("test", "counter.count = 10;")
+            .is_err());
+
+        // Methods accessed twice are equal, like methods of objects.
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "test",
+                    "let other = counter.increment; other == counter.increment;"
+                )
+                .unwrap(),
+            Value::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn memory_loader() {
+        let loader = MemoryLoader::new()
+            .with("lib/math.regis", "export let PI = 3;".into())
+            .with(
+                "lib/util.regis",
+                "let Math = @import(\"./math.regis\"); export fn tau() { return Math.PI * 2; }"
+                    .into(),
+            );
+
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+        assert_eq!(
+            interpreter
+                .eval_source("main", "@import(\"./lib/util.regis\").tau();")
+                .unwrap(),
+            Value::Int(6)
+        );
+        assert!(matches!(
+            interpreter
+                .eval_source("main", "@import(\"./lib/missing.regis\");")
+                .unwrap_err()
+                .variant(),
+            RegisErrorVariant::ModuleDoesNotExistError { .. }
+        ));
+    }
+
+    #[test]
+    fn native_module() {
+        let mut interpreter = interpreter();
+        interpreter.register_native_module(
+            "std:math",
+            NativeModule::new()
+                .with_value("PI", Value::Int(3))
+                .with_typed_function("max", |a: i64, b: i64| Ok(a.max(b))),
+        );
+
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "let Math = @import(\"std:math\"); Math.max(Math.PI, 2);"
+                )
+                .unwrap(),
+            Value::Int(3)
+        );
+        assert_eq!(
+            interpreter
+                .eval_source("main", "@import(\"std:math\") == @import(\"std:math\");")
+                .unwrap(),
+            Value::Boolean(true)
+        );
+    }
+
+    #[test]
+    fn circular_import() {
+        let loader = MemoryLoader::new()
+            .with(
+                "a.regis",
+                "@import(\"./b.regis\"); export let a = 1;".into(),
+            )
+            .with("b.regis", "export let b = @import(\"./a.regis\").a;".into());
+
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+        let error = interpreter
+            .eval_source("main", "@import(\"./a.regis\");")
+            .unwrap_err();
+
+        match error.variant() {
+            RegisErrorVariant::CircularImportError { chain } => {
+                assert_eq!(chain, &["a.regis", "b.regis", "a.regis"]);
+            }
+            other => panic!("Expected circular import error. Got {:?}.", other),
+        }
+
+        let loader = MemoryLoader::new()
+            .with(
+                "c.regis",
+                "import { d } from \"./d.regis\"; export let c = 1;".into(),
+            )
+            .with(
+                "d.regis",
+                "import { c } from \"./c.regis\"; export let d = 1;".into(),
+            );
+
+        interpreter.set_loader(loader);
+        assert!(matches!(
+            interpreter
+                .eval_source("main", "import { c } from \"./c.regis\";")
+                .unwrap_err()
+                .variant(),
+            RegisErrorVariant::CircularImportError { .. }
+        ));
+    }
+
+    #[test]
+    fn failed_import_is_not_cached() {
+        let loader = MemoryLoader::new().with("a.regis", "export let a = 1 + null;".into());
+
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+        for _ in 0..2 {
+            assert!(matches!(
+                interpreter
+                    .eval_source("main", "@import(\"./a.regis\");")
+                    .unwrap_err()
+                    .variant(),
+                RegisErrorVariant::UndefinedBinaryOperation { .. }
+            ));
+        }
+
+        assert_eq!(interpreter.frames.len(), 1);
+        assert_eq!(interpreter.top(), 0);
+    }
+
+    #[test]
+    fn static_import() {
+        let loader = MemoryLoader::new()
+            .with(
+                "math.regis",
+                "export let PI = 3; export fn double(x) { return x * 2; }".into(),
+            )
+            .with(
+                "lib.regis",
+                "export { PI as pi, double } from \"./math.regis\";".into(),
+            );
+
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+        interpreter.register_native_module(
+            "std:math",
+            NativeModule::new().with_typed_function("max", |a: i64, b: i64| Ok(a.max(b))),
+        );
+
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "import { pi, double as twice } from \"./lib.regis\";
+                    import * as Math from \"./math.regis\";
+                    import { max } from \"std:math\";
+                    fn run() => max(twice(pi), Math.PI);
+                    run();"
+                )
+                .unwrap(),
+            Value::Int(6)
+        );
+
+        let error = interpreter
+            .eval_source("main", "import { tau } from \"./math.regis\";")
+            .unwrap_err();
+        assert!(matches!(
+            error.variant(),
+            RegisErrorVariant::ExportDoesNotExistError { .. }
+        ));
+
+        // Imported variables are read-only, also when assigned inside a function.
+        for source in [
+            "import { PI } from \"./math.regis\"; PI = 4;",
+            "import { PI } from \"./math.regis\"; fn f() { PI = 4; }",
+        ] {
+            let error = interpreter.eval_source("main", source).unwrap_err();
+            assert!(matches!(
+                error.variant(),
+                RegisErrorVariant::TypeError { .. }
+            ));
+            assert!(error.location().is_some());
+        }
+
+        // `from` and `as` are only keywords inside import and export statements.
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "import { PI as as } from \"./math.regis\";
+                    let from = 2;
+                    fn f(as) => as * from;
+                    f(as);"
+                )
+                .unwrap(),
+            Value::Int(6)
+        );
+    }
+
+    #[test]
+    fn data_module() {
+        let loader = MemoryLoader::new()
+            .with(
+                "config.json",
+                r#"{ "name": "regis", "ports": [80, 443], "debug": false, "ratio": 0.5 }"#.into(),
+            )
+            .with("invalid.json", "{ \"name\": }".into())
+            .with("empty.json", "".into());
+
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "import { ports } from \"./config.json\";
+                    let config = @import(\"./config.json\");
+                    config.name + \":\" + ports[1] + \":\" + config.debug + \":\" + config.ratio;"
+                )
+                .unwrap(),
+            Value::String("regis:443:false:0.5".to_string().into())
+        );
+
+        // Data modules are cached in the module table like any other module.
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "@import(\"./config.json\") == @import(\"./config.json\");"
+                )
+                .unwrap(),
+            Value::Boolean(true)
+        );
+
+        let error = interpreter
+            .eval_source("main", "@import(\"./invalid.json\");")
+            .unwrap_err();
+        assert!(matches!(
+            error.variant(),
+            RegisErrorVariant::ParseError { .. }
+        ));
+
+        // Errors in empty files can still be shown with their source.
+        let error = interpreter
+            .eval_source("main", "import * as empty from \"./empty.json\";")
+            .unwrap_err();
+        assert!(matches!(
+            error.variant(),
+            RegisErrorVariant::ParseError { .. }
+        ));
+        assert!(error.show(Some("")).contains("empty.json"));
+    }
+
+    #[test]
+    fn reload_module() {
+        let mut interpreter = Interpreter::with_loader(
+            CanonicalPath::from_virtual("main"),
+            MemoryLoader::new().with("lib.regis", "export fn greet() { return 1; }".into()),
+        );
+
+        let run = interpreter
+            .eval_source(
+                "main",
+                "import { greet } from \"./lib.regis\";
+                let Lib = @import(\"./lib.regis\");
+                fn run() => greet() * 10 + Lib.greet();
+                run;",
+            )
+            .unwrap();
+        assert_eq!(interpreter.call(&run, &[]).unwrap(), Value::Int(11));
+
+        let path = interpreter
+            .loader()
+            .resolve(
+                "./lib.regis",
+                &CanonicalPath::from_virtual("main").parent(),
+                &[],
+            )
+            .unwrap();
+
+        // Existing references to the module's exports see the reloaded functions.
+        interpreter.set_loader(
+            MemoryLoader::new().with("lib.regis", "export fn greet() { return 2; }".into()),
+        );
+        interpreter.reload_module(&path).unwrap();
+        assert_eq!(interpreter.call(&run, &[]).unwrap(), Value::Int(22));
+
+        // A module that fails to reload keeps its previous exports.
+        interpreter.set_loader(MemoryLoader::new().with("lib.regis", "export fn".into()));
+        assert!(interpreter.reload_module(&path).is_err());
+        assert_eq!(interpreter.call(&run, &[]).unwrap(), Value::Int(22));
+    }
+
+    #[test]
+    fn sandbox() {
+        let loader = MemoryLoader::new().with("lib.regis", "export let x = 1;".into());
+        let mut interpreter = Interpreter::with_config(
+            CanonicalPath::from_virtual("main"),
+            loader,
+            InterpreterConfig::sandboxed(),
+        );
+        interpreter.register_native_module(
+            "std:math",
+            NativeModule::new().with_value("PI", Value::Int(3)),
+        );
+
+        for source in &["@println(1);", "@sleep(0);", "@worker(\"./lib.regis\");"] {
+            let error = interpreter.eval_source("main", source).unwrap_err();
+            assert!(matches!(
+                error.variant(),
+                RegisErrorVariant::PermissionDenied { .. }
+            ));
+        }
+
+        // Native modules and modules in memory don't touch the filesystem, so they can still be
+        // imported.
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "import { x } from \"./lib.regis\"; @import(\"std:math\").PI + x;"
+                )
+                .unwrap(),
+            Value::Int(4)
+        );
+
+        let mut interpreter = Interpreter::with_config(
+            CanonicalPath::from_virtual("main"),
+            FileSystemLoader::new(),
+            InterpreterConfig::sandboxed(),
+        );
+        for source in &[
+            "@import(\"./lib.regis\");",
+            "import { x } from \"./lib.regis\";",
+        ] {
+            let error = interpreter.eval_source("main", source).unwrap_err();
+            assert!(matches!(
+                error.variant(),
+                RegisErrorVariant::PermissionDenied { .. }
+            ));
+        }
+    }
+
+    #[test]
+    fn import_root() {
+        let loader = MemoryLoader::new()
+            .with("app/lib.regis", "export let x = 1;".into())
+            .with("secret.regis", "export let x = 2;".into());
+        let mut interpreter = Interpreter::with_config(
+            CanonicalPath::from_virtual("app/main"),
+            loader,
+            InterpreterConfig::new().with_import_root(CanonicalPath::from_virtual("app")),
+        );
+
+        assert_eq!(
+            interpreter
+                .eval_source("app/main", "@import(\"./lib.regis\").x;")
+                .unwrap(),
+            Value::Int(1)
+        );
+
+        let error = interpreter
+            .eval_source("app/main", "@import(\"../secret.regis\").x;")
+            .unwrap_err();
+        assert!(matches!(
+            error.variant(),
+            RegisErrorVariant::PermissionDenied { .. }
+        ));
+    }
+
+    #[test]
+    fn execution_limits() {
+        fn limit_error(config: InterpreterConfig, source: &str) -> RegisErrorVariant {
+            let mut interpreter = Interpreter::with_config(
+                CanonicalPath::from_virtual("main"),
+                MemoryLoader::new(),
+                config,
+            );
+            let error = interpreter.eval_source("main", source).unwrap_err();
+
+            // The interpreter is left in a usable state after hitting a limit.
+            assert_eq!(interpreter.frames.len(), 1);
+            assert_eq!(interpreter.top(), 0);
+            assert_eq!(
+                interpreter.eval_source("main", "1;").unwrap(),
+                Value::Int(1)
+            );
+
+            error.variant().clone()
+        }
+
+        assert!(matches!(
+            limit_error(
+                InterpreterConfig::new().with_max_instructions(1000),
+                "while true {}"
+            ),
+            RegisErrorVariant::InstructionLimitError { limit: 1000 }
+        ));
+        assert!(matches!(
+            limit_error(
+                InterpreterConfig::new().with_max_call_depth(50),
+                "fn f(n) => f(n + 1); f(0);"
+            ),
+            RegisErrorVariant::CallDepthLimitError { limit: 50 }
+        ));
+        assert!(matches!(
+            limit_error(
+                InterpreterConfig::new().with_max_stack_size(100),
+                "fn f(n) => f(n + 1); f(0);"
+            ),
+            RegisErrorVariant::StackLimitError { limit: 100 }
+        ));
+        assert!(matches!(
+            limit_error(
+                InterpreterConfig::new().with_max_allocations(100),
+                "let list = []; while true { list = [list]; }"
+            ),
+            RegisErrorVariant::HeapLimitError { limit: 100 }
+        ));
+        assert!(matches!(
+            limit_error(
+                InterpreterConfig::new().with_max_heap_size(1 << 20),
+                "let s = \"s\"; while true { s = s + s; }"
+            ),
+            RegisErrorVariant::HeapSizeLimitError { limit } if limit == 1 << 20
+        ));
+
+        // Recursion is limited by default, before it can overflow the native stack.
+        assert!(matches!(
+            limit_error(InterpreterConfig::new(), "fn f(n) => f(n + 1); f(0);"),
+            RegisErrorVariant::CallDepthLimitError { .. }
+        ));
+        assert!(matches!(
+            limit_error(InterpreterConfig::sandboxed(), "fn f(n) => f(n + 1); f(0);"),
+            RegisErrorVariant::CallDepthLimitError { .. }
+        ));
+
+        // Counters start over for each run, and allocations that were freed don't count.
+        let mut interpreter = Interpreter::with_config(
+            CanonicalPath::from_virtual("main"),
+            MemoryLoader::new(),
+            InterpreterConfig::new()
+                .with_max_instructions(1000)
+                .with_max_allocations(100),
+        );
+        let function = interpreter
+            .eval_source(
+                "main",
+                "fn f(n) { let i = 0; while i < n { [[[[i]]]]; i = i + 1; } } f;",
+            )
+            .unwrap();
+        for _ in 0..5 {
+            interpreter
+                .eval_source("main", "let i = 0; while i < 40 { [[[[i]]]]; i = i + 1; }")
+                .unwrap();
+            interpreter.call(&function, &[Value::Int(40)]).unwrap();
+        }
+        assert!(interpreter.instruction_count() <= 1000);
+    }
+
+    #[test]
+    fn interrupt() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        fn ignore_errors(
+            arguments: &[Value],
+            context: &mut ExternalCallContext,
+        ) -> Result<Value, RegisError> {
+            Ok(context.call(&arguments[0], &[]).unwrap_or(Value::Null))
+        }
+
+        let loader = MemoryLoader::new()
+            .with("idle.regis", "@recv(@parent);".into())
+            .with("spin.regis", "while true {}".into());
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+        let handle = interpreter.interrupt_handle();
+        assert_send(&handle);
+        interpreter.add_global_function("@ignore_errors".into(), 1, ignore_errors);
+
+        // Interrupts raised between runs don't stop the next one.
+        handle.interrupt();
+        assert_eq!(
+            interpreter.eval_source("main", "1;").unwrap(),
+            Value::Int(1)
+        );
+
+        // Builtins that block are interrupted as well, and native functions can't swallow it.
+        for source in &[
+            "while true {}",
+            "@sleep(60);",
+            "@recv(@worker(\"./idle\"));",
+            "@ignore_errors(fn() { while true {} }); while true {}",
+        ] {
+            let handle = handle.clone();
+            let thread = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                handle.interrupt();
+            });
+
+            let error = interpreter.eval_source("main", source).unwrap_err();
+            thread.join().unwrap();
+
+            assert!(matches!(error.variant(), RegisErrorVariant::Interrupted));
+            assert_eq!(interpreter.frames.len(), 1);
+            assert_eq!(interpreter.top(), 0);
+            assert_eq!(
+                interpreter.eval_source("main", "1;").unwrap(),
+                Value::Int(1)
+            );
+        }
+
+        // Workers are interrupted along with the interpreter that started them.
+        let spin = interpreter
+            .eval_source("main", "@worker(\"./spin\");")
+            .unwrap();
+        let recv = interpreter
+            .eval_source("main", "fn receive(worker) => @recv(worker); receive;")
+            .unwrap();
+        let thread = {
+            let handle = handle.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                handle.interrupt();
+            })
+        };
+        let error = interpreter
+            .eval_source("main", "while true {}")
+            .unwrap_err();
+        thread.join().unwrap();
+        assert!(matches!(error.variant(), RegisErrorVariant::Interrupted));
+        let error = interpreter.call(&recv, &[spin]).unwrap_err();
+        assert!(matches!(
+            error.variant(),
+            RegisErrorVariant::WorkerError { .. }
+        ));
+
+        // Dropping a worker interrupts it and waits for it to stop.
+        assert_eq!(
+            interpreter
+                .eval_source("main", "fn f() { @worker(\"./spin\"); } f(); 1;")
+                .unwrap(),
+            Value::Int(1)
+        );
+    }
+
+    #[test]
+    fn redirect_io() {
+        let output = CaptureBuffer::new();
+        let mut interpreter = interpreter();
+        interpreter.set_output(output.clone());
+        interpreter.set_input(&b"regis\r\nlast"[..]);
+
+        interpreter
+            .eval_source(
+                "main",
+                "@print(\"name? \");
+                @println(\"hello \" + @readline());
+                @println(@readline());
+                @println(@readline());",
+            )
+            .unwrap();
+
+        assert_eq!(output.contents(), "name? hello regis\nlast\nnull\n");
+    }
+
+    #[test]
+    fn garbage_collection() {
+        let mut interpreter = interpreter();
+        let kept = interpreter
+            .eval_source(
+                "main",
+                "let kept = {};
+                kept.me = kept;
+                fn leak() {
+                    let object = {};
+                    object.list = [object];
+                    object.get = fn() => object;
+                }
+                leak();
+                kept;",
+            )
+            .unwrap();
+
+        // The object, list, closure and the closure's capture of the object are unreachable.
+        assert_eq!(interpreter.gc(), 4);
+        assert_eq!(
+            interpreter.eval_source("main", "@gc();").unwrap(),
+            Value::Int(0)
+        );
+
+        let stats = interpreter.gc_stats();
+        assert_eq!(stats.collections, 2);
+        assert_eq!(stats.collected, 4);
+
+        // Cycles that are still reachable are left alone.
+        match &kept {
+            Value::Object(kept) => assert_eq!(kept.borrow().len(), 1),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn closure_captures_enclosing_function() {
+        let mut interpreter = interpreter();
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "fn counter() { let count = 0; return fn() { count = count + 1; return count; }; }
+                    let next = counter();
+                    next();
+                    next();"
+                )
+                .unwrap(),
+            Value::Int(2)
+        );
+    }
+
+    #[test]
+    fn heap_stats() {
+        let mut interpreter = interpreter();
+        let before = interpreter.heap_stats();
+
+        let list = interpreter
+            .eval_source("main", "[\"regis\", { name: \"regis\" }, [], fn() => 1];")
+            .unwrap();
+        let during = interpreter.heap_stats();
+        assert_eq!(during.lists.count, before.lists.count + 2);
+        assert_eq!(during.objects.count, before.objects.count + 2);
+        assert_eq!(during.functions.count, before.functions.count + 1);
+        assert!(during.strings.count >= before.strings.count + 2);
+        assert!(during.lists.bytes > before.lists.bytes);
+
+        // Peaks are kept after the values are freed.
+        drop(list);
+        let after = interpreter.heap_stats();
+        assert_eq!(after.lists.count, before.lists.count);
+        assert_eq!(after.lists.peak_count, during.lists.count);
+        assert_eq!(after.lists.peak_bytes, during.lists.bytes);
+
+        // Peaks also include allocations that were freed before the heap was measured.
+        interpreter
+            .eval_source("main", "fn f() { let a = [[1], [2], [3], [4]]; } f();")
+            .unwrap();
+        let after = interpreter.heap_stats();
+        assert_eq!(after.lists.count, before.lists.count);
+        assert!(after.lists.peak_count >= before.lists.count + 5);
+
+        // Temporaries that are freed right away don't add up.
+        interpreter
+            .eval_source("main", "let i = 0; while i < 100 { [i]; i = i + 1; }")
+            .unwrap();
+        assert!(interpreter.heap_stats().lists.peak_count < before.lists.count + 10);
+
+        // Cycles freed by automatic collections count towards the peaks.
+        let previous = interpreter.heap_stats();
+        interpreter
+            .eval_source(
+                "main",
+                "fn f() { let i = 0; while i < 2000 { let a = {}; a.self = a; i = i + 1; } } f();",
+            )
+            .unwrap();
+        assert!(interpreter.gc_stats().collections > 0);
+        interpreter.gc();
+        let after = interpreter.heap_stats();
+        assert_eq!(after.objects.count, previous.objects.count);
+        assert!(after.objects.peak_count >= previous.objects.count + 500);
+
+        assert_eq!(
+            interpreter
+                .eval_source("main", "let stats = @heap_stats(); stats.lists.count;")
+                .unwrap(),
+            Value::Int(before.lists.count as i64)
+        );
+    }
+
+    #[test]
+    fn workers() {
+        let loader = MemoryLoader::new()
+            .with(
+                "double.regis",
+                "let request = @recv(@parent);
+                while request != null {
+                    @send(@parent, { value: request.value * 2, tags: request.tags + [\"doubled\"] });
+                    request = @recv(@parent);
+                }"
+                .into(),
+            )
+            .with("failing.regis", "@send(@parent, 1); 1 + null;".into());
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "let worker = @worker(\"./double\");
+                    let request = { value: 21, tags: [\"answer\"] };
+                    @send(worker, request);
+                    let response = @recv(worker);
+                    response.value + \":\" + response.tags[1] + \":\" + @len(request.tags);"
+                )
+                .unwrap(),
+            Value::String("42:doubled:1".to_string().into())
+        );
+
+        // Messages are copies, so only plain data can be sent.
+        for source in &[
+            "@send(@worker(\"./double\"), fn() => 1);",
+            "let list = []; list = [list]; list[0] = list; @send(@worker(\"./double\"), list);",
+            "@send(@worker(\"./double\"), @worker(\"./double\"));",
+        ] {
+            let error = interpreter.eval_source("main", source).unwrap_err();
+            assert!(matches!(
+                error.variant(),
+                RegisErrorVariant::TypeError { .. }
+            ));
+        }
+
+        // Messages sent before a worker fails are still received, then its error is raised.
+        let error = interpreter
+            .eval_source(
+                "main",
+                "let worker = @worker(\"./failing\"); @recv(worker); @recv(worker);",
+            )
+            .unwrap_err();
+        assert!(matches!(
+            error.variant(),
+            RegisErrorVariant::WorkerError { .. }
+        ));
+
+        assert_eq!(
+            interpreter.eval_source("main", "@parent;").unwrap(),
+            Value::Null
+        );
+    }
+
+    #[test]
+    fn disassemble() {
+        let source = "let i = 0;
+            while i < 3 {
+                i = i + 1;
+            }
+            export fn add(a, b) {
+                return a + b;
+            }
+            @println(add(i, 1.5));";
+        let loader = MemoryLoader::new().with("main.regis", source.into());
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+
+        let module = interpreter
+            .compile_module(&CanonicalPath::from_virtual("main.regis"))
+            .unwrap();
+        let output = crate::bytecode::Disassembler::new(source).disassemble_module(&module);
+        let lines = output.lines().map(str::trim).collect::<Vec<_>>();
+
+        // Compiling doesn't run the module.
+        assert!(interpreter.module_paths().next().is_none());
+
+        assert!(lines.contains(&"variables: i"));
+        assert!(lines.contains(&"L0:"));
+        assert!(lines.contains(&"2      1  PushInt         0"));
+        assert!(lines.contains(&"3         AssignVariable  0 (i)"));
+        assert!(lines.contains(&"7         JumpIf          L1"));
+        assert!(lines.contains(&"13      2  Jump            L0"));
+        assert!(lines.contains(&"15         PushFloat       1.5"));
+        assert!(lines.contains(&"18         PushGlobal      1 (@println)"));
+        assert!(lines.contains(&"function add(a, b)"));
+        assert!(lines.contains(&"variables: a, b"));
+
+        // Imported modules aren't run either. What they export is read from their source.
+        let source = "import { greet } from \"./lib.regis\"; greet();";
+        interpreter.set_loader(
+            MemoryLoader::new()
+                .with("app.regis", source.into())
+                .with("bad.regis", "import { tau } from \"./lib.regis\";".into())
+                .with(
+                    "lib.regis",
+                    "@println(\"loaded\"); export fn greet() { return 1; }".into(),
+                ),
+        );
+        let module = interpreter
+            .compile_module(&CanonicalPath::from_virtual("app.regis"))
+            .unwrap();
+        let output = crate::bytecode::Disassembler::new(source).disassemble_module(&module);
+        assert!(output.contains("PushExport"));
+        assert!(interpreter.module_paths().next().is_none());
+
+        let error = interpreter
+            .compile_module(&CanonicalPath::from_virtual("bad.regis"))
+            .unwrap_err();
+        assert!(matches!(
+            error.variant(),
+            RegisErrorVariant::ExportDoesNotExistError { .. }
+        ));
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn workers_share_output_and_native_modules() {
+        let loader = MemoryLoader::new()
+            .with(
+                "worker.regis",
+                "import { max, base } from \"std:math\";
+                @println(max(base, 2));
+                @send(@parent, null);"
+                    .into(),
+            )
+            .with(
+                "printer.regis",
+                "let i = 0; while i < 100 { @println(i); i = i + 1; }".into(),
+            );
+        let output = CaptureBuffer::new();
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+        interpreter.set_output(output.clone());
+        interpreter.register_native_module(
+            "std:math",
+            NativeModule::new()
+                .with_typed_function("max", |a: i64, b: i64| Ok(a.max(b)))
+                .with_value("base", Value::Int(3)),
+        );
+
+        interpreter
+            .eval_source("main", "let worker = @worker(\"./worker\"); @recv(worker);")
+            .unwrap();
+        assert_eq!(output.contents(), "3\n");
+
+        // Workers printing at the same time wait for each other.
+        output.clear();
+        interpreter
+            .eval_source(
+                "main",
+                "let first = @worker(\"./printer\");
+                let second = @worker(\"./printer\");
+                let i = 0;
+                while i < 100 { @println(i); i = i + 1; }
+                @recv(first);
+                @recv(second);",
+            )
+            .unwrap();
+        assert_eq!(output.contents().lines().count(), 300);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn interpreter_is_send() {
+        let mut interpreter = interpreter();
+        let value = interpreter.eval_source("main", "[1, { a: 2 }];").unwrap();
+
+        let thread = thread::spawn(move || {
+            let result = interpreter.eval_source("main", "3;").unwrap();
+            (interpreter, value, result)
+        });
+        let (_, value, result) = thread.join().unwrap();
+
+        assert_eq!(value.to_string(), "[1, { a: 2 }]");
+        assert_eq!(result, Value::Int(3));
+    }
+
+    #[test]
+    fn search_paths() {
+        let loader = MemoryLoader::new()
+            .with("packages/strings/index.regis", "export let a = 1;".into())
+            .with("packages/strings/extra.regis", "export let b = 2;".into())
+            .with("local.regis", "export let c = 3;".into())
+            .with(
+                "app/main.regis",
+                "import { d } from \"helper\"; export let e = d;".into(),
+            )
+            .with("app/helper.regis", "export let d = 4;".into())
+            .with("packages/helper.regis", "export let d = 5;".into());
+
+        let mut interpreter = Interpreter::with_loader(CanonicalPath::from_virtual("main"), loader);
+        interpreter.add_search_path(CanonicalPath::from_virtual("packages"));
+
+        assert_eq!(
+            interpreter
+                .eval_source(
+                    "main",
+                    "import { a } from \"strings\";
+                    import { b } from \"strings/extra\";
+                    import { c } from \"./local\";
+                    a + b + c;"
+                )
+                .unwrap(),
+            Value::Int(6)
+        );
+
+        // Paths that aren't explicitly relative are looked up next to the importing module first.
+        assert_eq!(
+            interpreter
+                .eval_source("main", "import { e } from \"./app/main\"; e;")
+                .unwrap(),
+            Value::Int(4)
+        );
+    }
+
+    #[test]
+    fn eval_source_parse_error() {
+        let mut interpreter = interpreter();
+        assert!(matches!(
+            interpreter
+                .eval_source("test", "let = 1;")
+                .unwrap_err()
+                .variant(),
+            RegisErrorVariant::ParseError { .. }
+        ));
+    }
+}
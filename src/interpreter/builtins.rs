@@ -1,24 +1,56 @@
 use core::panic;
+use std::io::Error as IOError;
 use std::time::Duration;
 
 use crate::error::{RegisError, RegisErrorVariant};
-use crate::source::{CanonicalPath, RelativePath};
+use crate::source::CanonicalPath;
 
+use super::convert::IntoValue;
 use super::function::ProcedureVariant;
 use super::native::ExternalCallContext;
 use super::value::Value;
-use super::FrameVariant;
+use super::worker::{Message, Worker};
+use super::{FrameVariant, Interpreter};
 
-pub fn print(arguments: &[Value], _: &mut ExternalCallContext) -> Result<Value, RegisError> {
-    print!("{}", arguments.first().unwrap());
+pub fn print(
+    arguments: &[Value],
+    ExternalCallContext { interpreter }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    interpreter.config().check_stdout()?;
+
+    // Flush since the output may not end with a newline, e.g. when prompting for @readline().
+    let output = interpreter.output();
+    write!(output, "{}", arguments.first().unwrap()).map_err(io_error)?;
+    output.flush().map_err(io_error)?;
     Ok(Value::Null)
 }
 
-pub fn println(arguments: &[Value], _: &mut ExternalCallContext) -> Result<Value, RegisError> {
-    println!("{}", arguments.first().unwrap());
+pub fn println(
+    arguments: &[Value],
+    ExternalCallContext { interpreter }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    interpreter.config().check_stdout()?;
+    writeln!(interpreter.output(), "{}", arguments.first().unwrap()).map_err(io_error)?;
     Ok(Value::Null)
 }
 
+pub fn readline(
+    _: &[Value],
+    ExternalCallContext { interpreter }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    interpreter.config().check_stdin()?;
+
+    // Returns the line without its line ending, or null once the input is exhausted.
+    let mut line = String::new();
+    if interpreter.input().read_line(&mut line).map_err(io_error)? == 0 {
+        return Ok(Value::Null);
+    }
+
+    let length = line.trim_end_matches(&['\r', '\n'][..]).len();
+    line.truncate(length);
+    Ok(Value::String(line.into()))
+}
+
 pub fn len(arguments: &[Value], _: &mut ExternalCallContext) -> Result<Value, RegisError> {
     Ok(Value::Int(match arguments.first().unwrap() {
         Value::String(string) => string.len(),
@@ -54,28 +86,9 @@ pub fn import(
         }
     };
 
-    let resolved = if let Some(relative) = RelativePath::from(&path) {
-        let root = match interpreter.top_frame().unwrap().variant() {
-            FrameVariant::Call(function) => match function.procedure() {
-                ProcedureVariant::Internal(procedure) => procedure.environment().path().parent(),
-                ProcedureVariant::External(..) => {
-                    panic!("@import() cannot be called from external functions.")
-                }
-            },
-            FrameVariant::Module(path) => path.parent(),
-        };
-
-        root.join(relative)
-    } else if let Some(canonical) = CanonicalPath::from(&path) {
-        Some(canonical)
-    } else {
-        None
-    };
-
-    if let Some(resolved) = resolved {
-        interpreter.load_module(&resolved)?;
-        let module = interpreter.modules.get(&resolved).unwrap();
-        Ok(Value::Object(module.exports().clone()))
+    let root = calling_directory(interpreter, "@import()");
+    if let Some(module) = interpreter.import_module(&path, &root)? {
+        Ok(interpreter.module_value(&module).unwrap().clone())
     } else {
         Err(RegisError::new(
             None,
@@ -84,7 +97,78 @@ pub fn import(
     }
 }
 
-pub fn sleep(arguments: &[Value], _: &mut ExternalCallContext) -> Result<Value, RegisError> {
+pub fn worker(
+    arguments: &[Value],
+    ExternalCallContext { interpreter }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    let path = match arguments.first().unwrap() {
+        Value::String(path) => path.to_string(),
+        other => {
+            return Err(RegisError::new(
+                None,
+                RegisErrorVariant::TypeError {
+                    message: format!(
+                        "Path passed to @worker() must be a string. Got '{}'.",
+                        other.type_of()
+                    ),
+                },
+            ))
+        }
+    };
+
+    interpreter.config().check_threads()?;
+
+    let root = calling_directory(interpreter, "@worker()");
+    match interpreter.resolve_module(&path, &root)? {
+        Some(path) => Ok(Value::Worker(Worker::spawn(interpreter, path)?.into())),
+        None => Err(RegisError::new(
+            None,
+            RegisErrorVariant::ModuleDoesNotExistError { path },
+        )),
+    }
+}
+
+pub fn send(
+    arguments: &[Value],
+    ExternalCallContext { .. }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    let worker = expect_worker(arguments.first().unwrap(), "@send()")?;
+    let message = Message::from_value(arguments.get(1).unwrap())?;
+    Ok(Value::Boolean(worker.send(message)))
+}
+
+pub fn recv(
+    arguments: &[Value],
+    ExternalCallContext { interpreter }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    let worker = expect_worker(arguments.first().unwrap(), "@recv()")?;
+    match worker.recv(&interpreter.interrupt)? {
+        Some(message) => Ok(message.into_value(interpreter)),
+        None => Ok(Value::Null),
+    }
+}
+
+pub fn gc(
+    _: &[Value],
+    ExternalCallContext { interpreter }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    Ok(Value::Int(interpreter.gc() as i64))
+}
+
+pub fn heap_stats(
+    _: &[Value],
+    ExternalCallContext { interpreter }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    let stats = interpreter.heap_stats();
+    Ok(stats.into_value(interpreter))
+}
+
+pub fn sleep(
+    arguments: &[Value],
+    ExternalCallContext { interpreter }: &mut ExternalCallContext,
+) -> Result<Value, RegisError> {
+    interpreter.config().check_time()?;
+
     let seconds = match arguments.first().unwrap() {
         Value::Int(seconds) if *seconds >= 0 => *seconds as f64,
         Value::Float(seconds) if *seconds >= 0.0 => *seconds,
@@ -101,6 +185,48 @@ pub fn sleep(arguments: &[Value], _: &mut ExternalCallContext) -> Result<Value,
         }
     };
 
-    std::thread::sleep(Duration::from_secs_f64(seconds));
+    interpreter
+        .interrupt
+        .sleep(Duration::from_secs_f64(seconds))?;
     Ok(Value::Null)
 }
+
+fn calling_directory(interpreter: &Interpreter, function: &str) -> CanonicalPath {
+    match interpreter.top_frame().unwrap().variant() {
+        FrameVariant::Call(caller) => match caller.procedure() {
+            ProcedureVariant::Internal(procedure) => procedure.environment().path().parent(),
+            ProcedureVariant::External(..) => {
+                panic!("{} cannot be called from external functions.", function)
+            }
+        },
+        FrameVariant::Module(path) => path.parent(),
+    }
+}
+
+fn expect_worker<'value>(
+    value: &'value Value,
+    function: &str,
+) -> Result<&'value Worker, RegisError> {
+    match value {
+        Value::Worker(worker) => Ok(worker),
+        other => Err(RegisError::new(
+            None,
+            RegisErrorVariant::TypeError {
+                message: format!(
+                    "First argument passed to {} must be a worker. Got '{}'.",
+                    function,
+                    other.type_of()
+                ),
+            },
+        )),
+    }
+}
+
+fn io_error(error: IOError) -> RegisError {
+    RegisError::new(
+        None,
+        RegisErrorVariant::IOError {
+            message: error.to_string(),
+        },
+    )
+}
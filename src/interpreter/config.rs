@@ -0,0 +1,208 @@
+use crate::error::{RegisError, RegisErrorVariant};
+use crate::source::CanonicalPath;
+
+// Every call recurses on the native stack, so calls are limited to a depth that fits in the 2 MiB
+// stacks threads get by default, even in debug builds.
+const DEFAULT_MAX_CALL_DEPTH: usize = 500;
+
+#[derive(Debug, Clone)]
+pub struct InterpreterConfig {
+    filesystem: bool,
+    time: bool,
+    stdout: bool,
+    stdin: bool,
+    threads: bool,
+    import_root: Option<CanonicalPath>,
+    max_instructions: Option<usize>,
+    max_call_depth: Option<usize>,
+    max_stack_size: Option<usize>,
+    max_allocations: Option<usize>,
+    max_heap_size: Option<usize>,
+}
+
+impl InterpreterConfig {
+    pub fn new() -> Self {
+        Self {
+            filesystem: true,
+            time: true,
+            stdout: true,
+            stdin: true,
+            threads: true,
+            import_root: None,
+            max_instructions: None,
+            max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH),
+            max_stack_size: None,
+            max_allocations: None,
+            max_heap_size: None,
+        }
+    }
+
+    pub fn sandboxed() -> Self {
+        Self {
+            filesystem: false,
+            time: false,
+            stdout: false,
+            stdin: false,
+            threads: false,
+            import_root: None,
+            max_instructions: None,
+            max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH),
+            max_stack_size: None,
+            max_allocations: None,
+            max_heap_size: None,
+        }
+    }
+
+    pub fn with_filesystem(mut self, allowed: bool) -> Self {
+        self.filesystem = allowed;
+        self
+    }
+
+    pub fn with_time(mut self, allowed: bool) -> Self {
+        self.time = allowed;
+        self
+    }
+
+    pub fn with_stdout(mut self, allowed: bool) -> Self {
+        self.stdout = allowed;
+        self
+    }
+
+    pub fn with_stdin(mut self, allowed: bool) -> Self {
+        self.stdin = allowed;
+        self
+    }
+
+    pub fn with_threads(mut self, allowed: bool) -> Self {
+        self.threads = allowed;
+        self
+    }
+
+    pub fn with_import_root(mut self, root: CanonicalPath) -> Self {
+        self.import_root = Some(root);
+        self
+    }
+
+    pub fn with_max_instructions(mut self, limit: usize) -> Self {
+        self.max_instructions = Some(limit);
+        self
+    }
+
+    pub fn with_max_call_depth(mut self, limit: usize) -> Self {
+        self.max_call_depth = Some(limit);
+        self
+    }
+
+    pub fn with_max_stack_size(mut self, limit: usize) -> Self {
+        self.max_stack_size = Some(limit);
+        self
+    }
+
+    pub fn with_max_allocations(mut self, limit: usize) -> Self {
+        self.max_allocations = Some(limit);
+        self
+    }
+
+    pub fn with_max_heap_size(mut self, limit: usize) -> Self {
+        self.max_heap_size = Some(limit);
+        self
+    }
+
+    pub fn filesystem(&self) -> bool {
+        self.filesystem
+    }
+
+    pub fn time(&self) -> bool {
+        self.time
+    }
+
+    pub fn stdout(&self) -> bool {
+        self.stdout
+    }
+
+    pub fn stdin(&self) -> bool {
+        self.stdin
+    }
+
+    pub fn threads(&self) -> bool {
+        self.threads
+    }
+
+    pub fn import_root(&self) -> &Option<CanonicalPath> {
+        &self.import_root
+    }
+
+    pub fn max_instructions(&self) -> Option<usize> {
+        self.max_instructions
+    }
+
+    pub fn max_call_depth(&self) -> Option<usize> {
+        self.max_call_depth
+    }
+
+    pub fn max_stack_size(&self) -> Option<usize> {
+        self.max_stack_size
+    }
+
+    pub fn max_allocations(&self) -> Option<usize> {
+        self.max_allocations
+    }
+
+    pub fn max_heap_size(&self) -> Option<usize> {
+        self.max_heap_size
+    }
+
+    pub fn check_filesystem(&self) -> Result<(), RegisError> {
+        check(self.filesystem, "filesystem")
+    }
+
+    pub fn check_time(&self) -> Result<(), RegisError> {
+        check(self.time, "time")
+    }
+
+    pub fn check_stdout(&self) -> Result<(), RegisError> {
+        check(self.stdout, "stdout")
+    }
+
+    pub fn check_stdin(&self) -> Result<(), RegisError> {
+        check(self.stdin, "stdin")
+    }
+
+    pub fn check_threads(&self) -> Result<(), RegisError> {
+        check(self.threads, "threads")
+    }
+
+    pub fn check_import(&self, path: &CanonicalPath) -> Result<(), RegisError> {
+        match &self.import_root {
+            Some(root) if !path.as_ref().starts_with(root) => Err(RegisError::new(
+                None,
+                RegisErrorVariant::PermissionDenied {
+                    message: format!(
+                        "Module '{}' is outside of the import root '{}'.",
+                        path, root
+                    ),
+                },
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Default for InterpreterConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn check(allowed: bool, capability: &str) -> Result<(), RegisError> {
+    if allowed {
+        Ok(())
+    } else {
+        Err(RegisError::new(
+            None,
+            RegisErrorVariant::PermissionDenied {
+                message: format!("Access to {} is not allowed.", capability),
+            },
+        ))
+    }
+}
@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use crate::error::{RegisError, RegisErrorVariant};
+
+use super::list::List;
+use super::native::ExternalCallContext;
+use super::object::Object;
+use super::value::Value;
+use super::Interpreter;
+
+pub trait FromValue: Sized {
+    fn expected() -> String;
+    fn from_value(value: &Value) -> Option<Self>;
+}
+
+pub trait IntoValue {
+    fn into_value(self, interpreter: &mut Interpreter) -> Value;
+}
+
+impl FromValue for Value {
+    fn expected() -> String {
+        "any".into()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        Some(value.clone())
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self, _: &mut Interpreter) -> Value {
+        self
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self, _: &mut Interpreter) -> Value {
+        Value::Null
+    }
+}
+
+impl FromValue for bool {
+    fn expected() -> String {
+        "boolean".into()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self, _: &mut Interpreter) -> Value {
+        Value::Boolean(self)
+    }
+}
+
+impl FromValue for i64 {
+    fn expected() -> String {
+        "int".into()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self, _: &mut Interpreter) -> Value {
+        Value::Int(self)
+    }
+}
+
+impl FromValue for f64 {
+    fn expected() -> String {
+        "float".into()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Int(value) => Some(*value as f64),
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self, _: &mut Interpreter) -> Value {
+        Value::Float(self)
+    }
+}
+
+impl FromValue for String {
+    fn expected() -> String {
+        "string".into()
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(value) => Some(value.clone_inner()),
+            _ => None,
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self, _: &mut Interpreter) -> Value {
+        Value::String(self.into())
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self, _: &mut Interpreter) -> Value {
+        Value::String(self.into())
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn expected() -> String {
+        format!("{} or null", T::expected())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self, interpreter: &mut Interpreter) -> Value {
+        match self {
+            Some(value) => value.into_value(interpreter),
+            None => Value::Null,
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn expected() -> String {
+        format!("list<{}>", T::expected())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::List(list) => list.borrow().iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self, interpreter: &mut Interpreter) -> Value {
+        let mut list = List::new(interpreter.generate_id());
+        list.reserve(self.len());
+        for value in self {
+            list.push(value.into_value(interpreter));
+        }
+
+        interpreter.track(Value::List(list.into()))
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn expected() -> String {
+        format!("object<{}>", T::expected())
+    }
+
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Object(object) => object
+                .borrow()
+                .iter()
+                .map(|(key, value)| Some((String::from_value(key)?, T::from_value(value)?)))
+                .collect(),
+            _ => None,
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for HashMap<String, T> {
+    fn into_value(self, interpreter: &mut Interpreter) -> Value {
+        let mut object = Object::new(interpreter.generate_id());
+        object.reserve(self.len());
+        for (key, value) in self {
+            object.set(Value::String(key.into()), value.into_value(interpreter));
+        }
+
+        interpreter.track(Value::Object(object.into()))
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($name:ident: $index:tt),+) => {
+        impl<$($name: FromValue),+> FromValue for ($($name,)+) {
+            fn expected() -> String {
+                format!("[{}]", vec![$($name::expected()),+].join(", "))
+            }
+
+            fn from_value(value: &Value) -> Option<Self> {
+                match value {
+                    Value::List(list) => {
+                        let list = list.borrow();
+                        if list.len() != [$($index),+].len() {
+                            return None;
+                        }
+
+                        let values = list.iter().collect::<Vec<_>>();
+                        Some(($($name::from_value(values[$index])?,)+))
+                    }
+                    _ => None,
+                }
+            }
+        }
+
+        impl<$($name: IntoValue),+> IntoValue for ($($name,)+) {
+            fn into_value(self, interpreter: &mut Interpreter) -> Value {
+                let mut list = List::new(interpreter.generate_id());
+                $(list.push(self.$index.into_value(interpreter));)+
+                interpreter.track(Value::List(list.into()))
+            }
+        }
+    };
+}
+
+impl_tuple!(A: 0);
+impl_tuple!(A: 0, B: 1);
+impl_tuple!(A: 0, B: 1, C: 2);
+impl_tuple!(A: 0, B: 1, C: 2, D: 3);
+
+pub trait TypedCallback<Arguments> {
+    fn arity() -> usize;
+    fn call(
+        &self,
+        name: &str,
+        arguments: &[Value],
+        context: &mut ExternalCallContext,
+    ) -> Result<Value, RegisError>;
+}
+
+macro_rules! impl_typed_callback {
+    ($($name:ident: $index:tt),*) => {
+        impl<Callback, R, $($name),*> TypedCallback<($($name,)*)> for Callback
+        where
+            Callback: Fn($($name),*) -> Result<R, RegisError>,
+            R: IntoValue,
+            $($name: FromValue),*
+        {
+            fn arity() -> usize {
+                let indices: &[usize] = &[$($index),*];
+                indices.len()
+            }
+
+            #[allow(unused_variables)]
+            fn call(
+                &self,
+                name: &str,
+                arguments: &[Value],
+                context: &mut ExternalCallContext,
+            ) -> Result<Value, RegisError> {
+                let result = self($(argument::<$name>(name, arguments, $index)?),*)?;
+                Ok(result.into_value(context.interpreter))
+            }
+        }
+    };
+}
+
+impl_typed_callback!();
+impl_typed_callback!(A: 0);
+impl_typed_callback!(A: 0, B: 1);
+impl_typed_callback!(A: 0, B: 1, C: 2);
+impl_typed_callback!(A: 0, B: 1, C: 2, D: 3);
+impl_typed_callback!(A: 0, B: 1, C: 2, D: 3, E: 4);
+impl_typed_callback!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+
+fn argument<T: FromValue>(name: &str, arguments: &[Value], index: usize) -> Result<T, RegisError> {
+    let value = &arguments[index];
+    T::from_value(value).ok_or_else(|| {
+        RegisError::new(
+            None,
+            RegisErrorVariant::TypeError {
+                message: format!(
+                    "Argument {} passed to {}() must be of type '{}'. Got '{}'.",
+                    index + 1,
+                    name,
+                    T::expected(),
+                    value.type_of()
+                ),
+            },
+        )
+    })
+}
@@ -0,0 +1,72 @@
+use serde_json::Value as JsonValue;
+
+use crate::error::{RegisError, RegisErrorVariant};
+use crate::source::{CanonicalPath, Location, Span};
+
+use super::list::List;
+use super::object::Object;
+use super::value::Value;
+use super::Interpreter;
+
+pub type ModuleHandler = fn(
+    source: &str,
+    path: &CanonicalPath,
+    interpreter: &mut Interpreter,
+) -> Result<Value, RegisError>;
+
+pub fn json(
+    source: &str,
+    path: &CanonicalPath,
+    interpreter: &mut Interpreter,
+) -> Result<Value, RegisError> {
+    match serde_json::from_str(source) {
+        Ok(value) => Ok(from_json(value, interpreter)),
+        Err(error) => {
+            // serde_json reports lines and columns starting at 1, convert them to a byte offset.
+            let offset = source
+                .split_inclusive('\n')
+                .take(error.line().saturating_sub(1))
+                .map(str::len)
+                .sum::<usize>()
+                + error.column().saturating_sub(1);
+
+            Err(RegisError::new(
+                Some(Location::new(Some(path.clone()), Span::at(offset))),
+                RegisErrorVariant::ParseError {
+                    message: error.to_string(),
+                },
+            ))
+        }
+    }
+}
+
+fn from_json(value: JsonValue, interpreter: &mut Interpreter) -> Value {
+    match value {
+        JsonValue::Null => Value::Null,
+        JsonValue::Bool(value) => Value::Boolean(value),
+        JsonValue::Number(number) => match number.as_i64() {
+            Some(value) => Value::Int(value),
+            None => Value::Float(number.as_f64().unwrap()),
+        },
+        JsonValue::String(value) => Value::String(value.into()),
+        JsonValue::Array(values) => {
+            let mut list = List::new(interpreter.generate_id());
+            list.reserve(values.len());
+            for value in values {
+                list.push(from_json(value, interpreter));
+            }
+
+            interpreter.track(Value::List(list.into()))
+        }
+        JsonValue::Object(values) => {
+            let mut object = Object::new(interpreter.generate_id());
+            object.reserve(values.len());
+            for (key, value) in values {
+                let value = from_json(value, interpreter);
+                object.set(Value::String(key.into()), value);
+            }
+
+            interpreter.track(Value::Object(object.into()))
+        }
+    }
+}
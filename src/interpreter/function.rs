@@ -12,11 +12,18 @@ pub struct Function {
     id: Rid,
     procedure: ProcedureVariant,
     init: Box<[StackValue]>,
+    // The user data and method name of a bound method. A new function is created each time a
+    // method is accessed, so these are compared instead of the function's own id.
+    binding: Option<(Rid, SharedImmutable<String>)>,
 }
 
 impl PartialEq for Function {
     fn eq(&self, other: &Self) -> bool {
-        self.id == other.id
+        match (&self.binding, &other.binding) {
+            (Some(binding), Some(other)) => binding == other,
+            (None, None) => self.id == other.id,
+            _ => false,
+        }
     }
 }
 
@@ -24,7 +31,10 @@ impl Eq for Function {}
 
 impl Hash for Function {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.id.hash(state)
+        match &self.binding {
+            Some(binding) => binding.hash(state),
+            None => self.id.hash(state),
+        }
     }
 }
 
@@ -54,6 +64,19 @@ impl Function {
             id,
             procedure,
             init: Box::new([]),
+            binding: None,
+        }
+    }
+
+    pub fn bound(
+        id: Rid,
+        procedure: ProcedureVariant,
+        receiver: Rid,
+        method: SharedImmutable<String>,
+    ) -> Self {
+        Self {
+            binding: Some((receiver, method)),
+            ..Self::new(id, procedure)
         }
     }
 
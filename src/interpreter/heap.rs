@@ -0,0 +1,574 @@
+use std::collections::{HashMap, HashSet};
+use std::mem::{size_of, size_of_val};
+
+use crate::shared::{SharedImmutable, SharedMutable, WeakImmutable, WeakMutable};
+
+use super::capture::Capture;
+use super::convert::IntoValue;
+use super::function::Function;
+use super::list::List;
+use super::object::Object;
+use super::value::Value;
+use super::{Interpreter, StackValue};
+
+const MINIMUM_THRESHOLD: usize = 1000;
+
+// How much the heap has to grow after a collection before the limits are checked with another one.
+const LIMIT_COUNT_STEP: usize = 100;
+const LIMIT_BYTES_STEP: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GcStats {
+    pub collections: usize,
+    pub collected: usize,
+    pub tracked: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapUsage {
+    pub count: usize,
+    pub bytes: usize,
+    pub peak_count: usize,
+    pub peak_bytes: usize,
+}
+
+impl HeapUsage {
+    fn add(&mut self, bytes: usize) {
+        self.count += 1;
+        self.bytes += bytes;
+    }
+
+    fn remove(&mut self, bytes: usize) {
+        self.count = self.count.saturating_sub(1);
+        self.bytes = self.bytes.saturating_sub(bytes);
+    }
+
+    fn resize(&mut self, from: usize, to: usize) {
+        self.bytes = self.bytes.saturating_sub(from) + to;
+    }
+
+    fn update_peak(&mut self) {
+        self.peak_count = self.peak_count.max(self.count);
+        self.peak_bytes = self.peak_bytes.max(self.bytes);
+    }
+
+    fn with_peak(self, previous: &HeapUsage) -> Self {
+        Self {
+            peak_count: self.count.max(previous.peak_count),
+            peak_bytes: self.bytes.max(previous.peak_bytes),
+            ..self
+        }
+    }
+}
+
+impl IntoValue for HeapUsage {
+    fn into_value(self, interpreter: &mut Interpreter) -> Value {
+        let mut object = Object::new(interpreter.generate_id());
+        object.set(Value::String("count".into()), Value::Int(self.count as i64));
+        object.set(Value::String("bytes".into()), Value::Int(self.bytes as i64));
+        object.set(
+            Value::String("peak_count".into()),
+            Value::Int(self.peak_count as i64),
+        );
+        object.set(
+            Value::String("peak_bytes".into()),
+            Value::Int(self.peak_bytes as i64),
+        );
+
+        interpreter.track(Value::Object(object.into()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    pub strings: HeapUsage,
+    pub lists: HeapUsage,
+    pub objects: HeapUsage,
+    pub functions: HeapUsage,
+    pub captures: HeapUsage,
+}
+
+impl IntoValue for HeapStats {
+    fn into_value(self, interpreter: &mut Interpreter) -> Value {
+        let mut object = Object::new(interpreter.generate_id());
+        for (name, usage) in &[
+            ("strings", self.strings),
+            ("lists", self.lists),
+            ("objects", self.objects),
+            ("functions", self.functions),
+            ("captures", self.captures),
+        ] {
+            let usage = usage.into_value(interpreter);
+            object.set(Value::String((*name).into()), usage);
+        }
+
+        interpreter.track(Value::Object(object.into()))
+    }
+}
+
+impl HeapStats {
+    fn bytes(&self) -> usize {
+        self.strings.bytes
+            + self.lists.bytes
+            + self.objects.bytes
+            + self.functions.bytes
+            + self.captures.bytes
+    }
+
+    fn update_peaks(&mut self) {
+        self.strings.update_peak();
+        self.lists.update_peak();
+        self.objects.update_peak();
+        self.functions.update_peak();
+        self.captures.update_peak();
+    }
+
+    fn usage_mut(&mut self, kind: HeapObjectKind) -> &mut HeapUsage {
+        match kind {
+            HeapObjectKind::String => &mut self.strings,
+            HeapObjectKind::List => &mut self.lists,
+            HeapObjectKind::Object => &mut self.objects,
+            HeapObjectKind::Function => &mut self.functions,
+            HeapObjectKind::Capture => &mut self.captures,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Heap {
+    // Every tracked allocation with its size when it was last measured.
+    tracked: Vec<(WeakHeapObject, usize)>,
+    threshold: usize,
+    collections: usize,
+    collected: usize,
+    usage: HeapStats,
+    // The allocations and bytes left after the last collection made for the limits, and whether
+    // they were still exceeded.
+    limit_check: (usize, usize, bool),
+}
+
+impl Heap {
+    pub fn new() -> Self {
+        Self {
+            tracked: Vec::new(),
+            threshold: MINIMUM_THRESHOLD,
+            collections: 0,
+            collected: 0,
+            usage: HeapStats::default(),
+            limit_check: (0, 0, false),
+        }
+    }
+
+    pub fn track(&mut self, value: &Value) {
+        if let Some(object) = HeapObject::from_value(value) {
+            self.insert(object);
+        }
+    }
+
+    pub fn track_capture(&mut self, capture: &SharedMutable<Capture>) {
+        self.insert(HeapObject::Capture(capture.clone()));
+    }
+
+    // Current usage and peaks are updated as allocations are tracked. Temporaries are usually freed
+    // before the next allocation, so freed allocations at the end are forgotten first. Anything
+    // freed out of order is only noticed when the heap is measured or collected.
+    fn insert(&mut self, object: HeapObject) {
+        while let Some((last, bytes)) = self.tracked.last() {
+            if !last.is_dropped() {
+                break;
+            }
+
+            self.usage.usage_mut(last.kind()).remove(*bytes);
+            self.tracked.pop();
+        }
+
+        let bytes = object.size();
+        let usage = self.usage.usage_mut(object.kind());
+        usage.add(bytes);
+        usage.update_peak();
+        self.tracked.push((object.downgrade(), bytes));
+    }
+
+    fn forget_freed(&mut self) {
+        let usage = &mut self.usage;
+        self.tracked.retain(|(object, bytes)| {
+            if object.is_dropped() {
+                usage.usage_mut(object.kind()).remove(*bytes);
+                false
+            } else {
+                true
+            }
+        });
+    }
+
+    pub fn count(&self) -> usize {
+        self.tracked.len()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.usage.bytes()
+    }
+
+    // Whether more than `max_count` allocations or `max_bytes` bytes are alive. Cycles that are no
+    // longer reachable don't count, so the heap is collected before deciding. Near a limit that
+    // would collect on every instruction, so the previous answer is kept until the heap has grown
+    // by a fixed amount.
+    pub fn exceeds(&mut self, max_count: Option<usize>, max_bytes: Option<usize>) -> bool {
+        let exceeded = |heap: &Heap| {
+            max_count.is_some_and(|limit| heap.count() > limit)
+                || max_bytes.is_some_and(|limit| heap.bytes() > limit)
+        };
+        if !exceeded(self) {
+            return false;
+        }
+
+        let (count, bytes, previous) = self.limit_check;
+        if self.count() < count + LIMIT_COUNT_STEP && self.bytes() < bytes + LIMIT_BYTES_STEP {
+            return previous;
+        }
+
+        self.collect();
+        let result = exceeded(self);
+        self.limit_check = (self.count(), self.bytes(), result);
+        result
+    }
+
+    pub fn should_collect(&self) -> bool {
+        self.tracked.len() >= self.threshold
+    }
+
+    pub fn stats(&self) -> GcStats {
+        GcStats {
+            collections: self.collections,
+            collected: self.collected,
+            tracked: self.tracked.len(),
+        }
+    }
+
+    // Measures every live allocation. Only strings built while running are tracked, so the rest are
+    // found by looking through the values in tracked allocations and the given roots. Their sizes
+    // only count towards the peaks from here on.
+    pub fn measure<'value, I: Iterator<Item = &'value Value>>(&mut self, roots: I) -> HeapStats {
+        self.forget_freed();
+        let objects = self
+            .tracked
+            .iter_mut()
+            .filter_map(|(object, bytes)| {
+                let object = object.upgrade()?;
+                *bytes = object.size();
+                Some(object)
+            })
+            .collect::<Vec<_>>();
+
+        let mut usage = HeapStats::default();
+        let mut strings = HashSet::new();
+        let mut add_string = |value: &Value| {
+            if let Value::String(string) = value {
+                if strings.insert(string.address()) {
+                    usage.strings.add(size_of::<String>() + string.len());
+                }
+            }
+        };
+
+        roots.for_each(&mut add_string);
+        for object in &objects {
+            object.for_each_value(&mut add_string);
+        }
+
+        for object in &objects {
+            if let HeapObject::String(..) = object {
+                continue;
+            }
+
+            usage.usage_mut(object.kind()).add(object.size());
+        }
+
+        self.usage = HeapStats {
+            strings: usage.strings.with_peak(&self.usage.strings),
+            lists: usage.lists.with_peak(&self.usage.lists),
+            objects: usage.objects.with_peak(&self.usage.objects),
+            functions: usage.functions.with_peak(&self.usage.functions),
+            captures: usage.captures.with_peak(&self.usage.captures),
+        };
+        self.usage
+    }
+
+    pub fn collect(&mut self) -> usize {
+        // Forget allocations that were already freed by reference counting. Lists and objects may
+        // have grown since they were tracked, so their sizes are refreshed and the peaks updated
+        // before the garbage is freed.
+        self.forget_freed();
+        let usage = &mut self.usage;
+        let objects = self
+            .tracked
+            .iter_mut()
+            .filter_map(|(object, bytes)| {
+                let object = object.upgrade()?;
+                let size = object.size();
+                usage.usage_mut(object.kind()).resize(*bytes, size);
+                *bytes = size;
+                Some(object)
+            })
+            .collect::<Vec<_>>();
+        usage.update_peaks();
+        let indices = objects
+            .iter()
+            .enumerate()
+            .map(|(index, object)| (object.address(), index))
+            .collect::<HashMap<_, _>>();
+
+        // Count the references to each object that don't come from other tracked objects. The
+        // strong reference held by `objects` is subtracted too. Anything left over is referenced
+        // from outside the heap, e.g. from the stack, a global or the host.
+        let mut external = objects
+            .iter()
+            .map(|object| object.strong_count() - 1)
+            .collect::<Vec<_>>();
+        for object in &objects {
+            object.for_each_child(|child| {
+                if let Some(index) = indices.get(&child.address()) {
+                    external[*index] -= 1;
+                }
+            });
+        }
+
+        // Mark everything reachable from an externally referenced object.
+        let mut reachable = vec![false; objects.len()];
+        let mut pending = (0..objects.len())
+            .filter(|index| external[*index] > 0)
+            .collect::<Vec<_>>();
+        while let Some(index) = pending.pop() {
+            if reachable[index] {
+                continue;
+            }
+
+            reachable[index] = true;
+            objects[index].for_each_child(|child| {
+                if let Some(child) = indices.get(&child.address()) {
+                    pending.push(*child);
+                }
+            });
+        }
+
+        // The rest are only referenced by each other. Clearing them breaks the cycles so reference
+        // counting frees them. The cleared values are dropped after every object has been cleared.
+        let mut garbage = Vec::new();
+        let mut collected = 0;
+        for (index, object) in objects.iter().enumerate() {
+            if !reachable[index] {
+                object.clear(&mut garbage);
+                collected += 1;
+            }
+        }
+
+        drop(garbage);
+        drop(objects);
+
+        self.forget_freed();
+        self.threshold = MINIMUM_THRESHOLD.max(self.tracked.len() * 2);
+        self.collections += 1;
+        self.collected += collected;
+        collected
+    }
+}
+
+impl Default for Heap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HeapObjectKind {
+    String,
+    List,
+    Object,
+    Function,
+    Capture,
+}
+
+enum HeapObject {
+    String(SharedImmutable<String>),
+    List(SharedMutable<List>),
+    Object(SharedMutable<Object>),
+    Function(SharedImmutable<Function>),
+    Capture(SharedMutable<Capture>),
+}
+
+impl HeapObject {
+    fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::String(string) => Some(HeapObject::String(string.clone())),
+            Value::List(list) => Some(HeapObject::List(list.clone())),
+            Value::Object(object) => Some(HeapObject::Object(object.clone())),
+            Value::Function(function) => Some(HeapObject::Function(function.clone())),
+            _ => None,
+        }
+    }
+
+    fn kind(&self) -> HeapObjectKind {
+        match self {
+            HeapObject::String(..) => HeapObjectKind::String,
+            HeapObject::List(..) => HeapObjectKind::List,
+            HeapObject::Object(..) => HeapObjectKind::Object,
+            HeapObject::Function(..) => HeapObjectKind::Function,
+            HeapObject::Capture(..) => HeapObjectKind::Capture,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            HeapObject::String(string) => size_of::<String>() + string.len(),
+            HeapObject::List(list) => size_of::<List>() + list.borrow().len() * size_of::<Value>(),
+            HeapObject::Object(object) => {
+                size_of::<Object>() + object.borrow().len() * 2 * size_of::<Value>()
+            }
+            HeapObject::Function(function) => size_of::<Function>() + size_of_val(function.init()),
+            HeapObject::Capture(..) => size_of::<Capture>(),
+        }
+    }
+
+    fn downgrade(&self) -> WeakHeapObject {
+        match self {
+            HeapObject::String(string) => WeakHeapObject::String(string.downgrade()),
+            HeapObject::List(list) => WeakHeapObject::List(list.downgrade()),
+            HeapObject::Object(object) => WeakHeapObject::Object(object.downgrade()),
+            HeapObject::Function(function) => WeakHeapObject::Function(function.downgrade()),
+            HeapObject::Capture(capture) => WeakHeapObject::Capture(capture.downgrade()),
+        }
+    }
+
+    fn address(&self) -> usize {
+        match self {
+            HeapObject::String(string) => string.address(),
+            HeapObject::List(list) => list.address(),
+            HeapObject::Object(object) => object.address(),
+            HeapObject::Function(function) => function.address(),
+            HeapObject::Capture(capture) => capture.address(),
+        }
+    }
+
+    fn strong_count(&self) -> usize {
+        match self {
+            HeapObject::String(string) => string.strong_count(),
+            HeapObject::List(list) => list.strong_count(),
+            HeapObject::Object(object) => object.strong_count(),
+            HeapObject::Function(function) => function.strong_count(),
+            HeapObject::Capture(capture) => capture.strong_count(),
+        }
+    }
+
+    fn for_each_child<F: FnMut(HeapObject)>(&self, mut callback: F) {
+        match self {
+            HeapObject::String(..) => {}
+            HeapObject::List(list) => list
+                .borrow()
+                .iter()
+                .filter_map(HeapObject::from_value)
+                .for_each(callback),
+            HeapObject::Object(object) => object
+                .borrow()
+                .iter()
+                .flat_map(|(key, value)| vec![key, value])
+                .filter_map(HeapObject::from_value)
+                .for_each(callback),
+            HeapObject::Function(function) => {
+                for value in function.init() {
+                    if let StackValue::Capture(capture) = value {
+                        callback(HeapObject::Capture(capture.clone()));
+                    }
+                }
+            }
+            HeapObject::Capture(capture) => {
+                if let Some(child) = HeapObject::from_value(capture.borrow().get()) {
+                    callback(child);
+                }
+            }
+        }
+    }
+
+    fn for_each_value<F: FnMut(&Value)>(&self, mut callback: F) {
+        match self {
+            // Strings are values themselves, so they're measured even if only the host holds them.
+            HeapObject::String(string) => callback(&Value::String(string.clone())),
+            HeapObject::List(list) => list.borrow().iter().for_each(callback),
+            HeapObject::Object(object) => {
+                for (key, value) in object.borrow().iter() {
+                    callback(key);
+                    callback(value);
+                }
+            }
+            HeapObject::Function(..) => {}
+            HeapObject::Capture(capture) => callback(capture.borrow().get()),
+        }
+    }
+
+    fn clear(&self, garbage: &mut Vec<Value>) {
+        match self {
+            // Strings don't refer to anything, so they're never part of a cycle.
+            HeapObject::String(..) => {}
+            HeapObject::List(list) => {
+                let mut list = list.borrow_mut();
+                garbage.extend(list.iter().cloned());
+                list.clear();
+            }
+            HeapObject::Object(object) => {
+                let mut object = object.borrow_mut();
+                for (key, value) in object.iter() {
+                    garbage.push(key.clone());
+                    garbage.push(value.clone());
+                }
+                object.clear();
+            }
+            // Functions can't be modified, but every cycle through one also passes through one of
+            // its captures.
+            HeapObject::Function(..) => {}
+            HeapObject::Capture(capture) => {
+                let mut capture = capture.borrow_mut();
+                garbage.push(capture.get().clone());
+                capture.set(Value::Null);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum WeakHeapObject {
+    String(WeakImmutable<String>),
+    List(WeakMutable<List>),
+    Object(WeakMutable<Object>),
+    Function(WeakImmutable<Function>),
+    Capture(WeakMutable<Capture>),
+}
+
+impl WeakHeapObject {
+    fn kind(&self) -> HeapObjectKind {
+        match self {
+            WeakHeapObject::String(..) => HeapObjectKind::String,
+            WeakHeapObject::List(..) => HeapObjectKind::List,
+            WeakHeapObject::Object(..) => HeapObjectKind::Object,
+            WeakHeapObject::Function(..) => HeapObjectKind::Function,
+            WeakHeapObject::Capture(..) => HeapObjectKind::Capture,
+        }
+    }
+
+    fn upgrade(&self) -> Option<HeapObject> {
+        match self {
+            WeakHeapObject::String(string) => string.upgrade().map(HeapObject::String),
+            WeakHeapObject::List(list) => list.upgrade().map(HeapObject::List),
+            WeakHeapObject::Object(object) => object.upgrade().map(HeapObject::Object),
+            WeakHeapObject::Function(function) => function.upgrade().map(HeapObject::Function),
+            WeakHeapObject::Capture(capture) => capture.upgrade().map(HeapObject::Capture),
+        }
+    }
+
+    fn is_dropped(&self) -> bool {
+        match self {
+            WeakHeapObject::String(string) => string.is_dropped(),
+            WeakHeapObject::List(list) => list.is_dropped(),
+            WeakHeapObject::Object(object) => object.is_dropped(),
+            WeakHeapObject::Function(function) => function.is_dropped(),
+            WeakHeapObject::Capture(capture) => capture.is_dropped(),
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::{RegisError, RegisErrorVariant};
+
+// How often builtins that block, like @sleep and @recv, check whether they were interrupted.
+pub(super) const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Clone, Default)]
+pub struct InterruptHandle {
+    flag: Arc<AtomicBool>,
+    // How often the handle was interrupted. Unlike the flag, this is never cleared, so workers can
+    // tell whether the interpreter that started them was interrupted since.
+    count: Arc<AtomicUsize>,
+    // The handle of the interpreter that started this worker, and its count at the time.
+    parent: Option<(Box<InterruptHandle>, usize)>,
+}
+
+impl InterruptHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn interrupt(&self) {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        self.flag.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_interrupted(&self) -> bool {
+        self.flag.load(Ordering::SeqCst) || self.parent_interrupted()
+    }
+
+    // Workers are stopped for good once any interpreter that led to them is interrupted.
+    fn parent_interrupted(&self) -> bool {
+        self.parent.as_ref().is_some_and(|(parent, count)| {
+            parent.count.load(Ordering::SeqCst) != *count || parent.parent_interrupted()
+        })
+    }
+
+    // A handle for a worker started by the interpreter this handle belongs to.
+    pub(super) fn child(&self) -> Self {
+        Self {
+            flag: Arc::default(),
+            count: Arc::default(),
+            parent: Some((Box::new(self.clone()), self.count.load(Ordering::SeqCst))),
+        }
+    }
+
+    // The flag stays set until the next run starts, so the interrupted run keeps failing until it
+    // has fully unwound, even if a native function catches the error. Workers are only interrupted
+    // when they're dropped, which may happen before they even start running, so their flags are
+    // never cleared.
+    pub(super) fn reset(&self) {
+        if self.parent.is_none() {
+            self.flag.store(false, Ordering::SeqCst);
+        }
+    }
+
+    pub(super) fn check(&self) -> Result<(), RegisError> {
+        if self.is_interrupted() {
+            Err(RegisError::new(None, RegisErrorVariant::Interrupted))
+        } else {
+            Ok(())
+        }
+    }
+
+    // Sleeps in short steps so an interrupt doesn't have to wait for the whole duration.
+    pub(super) fn sleep(&self, duration: Duration) -> Result<(), RegisError> {
+        let end = Instant::now() + duration;
+        loop {
+            self.check()?;
+
+            let now = Instant::now();
+            if now >= end {
+                return Ok(());
+            }
+
+            std::thread::sleep(POLL_INTERVAL.min(end - now));
+        }
+    }
+}
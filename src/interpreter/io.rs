@@ -0,0 +1,105 @@
+use std::fmt::{Debug, Formatter, Result as FormatResult};
+use std::io::{BufRead, Result as IOResult, Write};
+
+use crate::shared::{MaybeSend, SharedMutable};
+
+pub trait Writer: Write + MaybeSend {}
+impl<W: Write + MaybeSend> Writer for W {}
+
+pub trait Reader: BufRead + MaybeSend {}
+impl<R: BufRead + MaybeSend> Reader for R {}
+
+// Workers write to the same output as the interpreter that started them, so the writer is shared.
+// Writes from different threads wait for each other.
+#[derive(Clone)]
+pub struct Output {
+    writer: SharedMutable<Box<dyn Writer>>,
+}
+
+impl Output {
+    pub fn new<W: Writer + 'static>(writer: W) -> Self {
+        Self {
+            writer: SharedMutable::new(Box::new(writer)),
+        }
+    }
+
+    pub fn writer(&mut self) -> &mut dyn Write {
+        self
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, bytes: &[u8]) -> IOResult<usize> {
+        self.writer.borrow_mut().write(bytes)
+    }
+
+    fn flush(&mut self) -> IOResult<()> {
+        self.writer.borrow_mut().flush()
+    }
+}
+
+impl Debug for Output {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "Output")
+    }
+}
+
+pub struct Input {
+    reader: Box<dyn Reader>,
+}
+
+impl Input {
+    pub fn new<R: Reader + 'static>(reader: R) -> Self {
+        Self {
+            reader: Box::new(reader),
+        }
+    }
+
+    pub fn reader(&mut self) -> &mut dyn BufRead {
+        self.reader.as_mut()
+    }
+}
+
+impl Debug for Input {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "Input")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptureBuffer {
+    buffer: SharedMutable<Vec<u8>>,
+}
+
+impl CaptureBuffer {
+    pub fn new() -> Self {
+        Self {
+            buffer: SharedMutable::new(Vec::new()),
+        }
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.buffer.borrow()).into_owned()
+    }
+
+    pub fn clear(&self) {
+        self.buffer.borrow_mut().clear();
+    }
+}
+
+impl Default for CaptureBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Write for CaptureBuffer {
+    fn write(&mut self, bytes: &[u8]) -> IOResult<usize> {
+        self.buffer.borrow_mut().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> IOResult<()> {
+        Ok(())
+    }
+}
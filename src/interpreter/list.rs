@@ -139,7 +139,15 @@ impl List {
         result.into()
     }
 
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
     pub fn push(&mut self, value: Value) {
         self.inner.push(value)
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Value> {
+        self.inner.iter()
+    }
 }
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+
+use crate::shared::MaybeSend;
+use crate::source::{CanonicalPath, RelativePath};
+
+const EXTENSION: &str = "regis";
+const INDEX: &str = "index.regis";
+
+pub trait ModuleLoader: Debug + MaybeSend {
+    fn resolve(
+        &self,
+        path: &str,
+        root: &CanonicalPath,
+        search_paths: &[CanonicalPath],
+    ) -> Option<CanonicalPath>;
+    fn load(&self, path: &CanonicalPath) -> Option<String>;
+
+    // Whether resolving and loading modules reads from the disk, which the interpreter's config
+    // can deny.
+    fn uses_filesystem(&self) -> bool {
+        false
+    }
+
+    // Creates a loader for a worker's interpreter, which runs on another thread. Loaders that
+    // can't be copied to another thread don't support workers.
+    fn fork(&self) -> Option<Box<dyn ModuleLoader + Send>> {
+        None
+    }
+}
+
+fn is_explicitly_relative(path: &str) -> bool {
+    path.starts_with("./") || path.starts_with("../")
+}
+
+fn bases(path: &str, root: &CanonicalPath, search_paths: &[CanonicalPath]) -> Vec<PathBuf> {
+    // Explicitly relative paths are resolved from the importing module's directory. Other
+    // relative paths are tried there first, then looked up in each of the search paths in order.
+    match RelativePath::from(&path) {
+        Some(relative) if is_explicitly_relative(path) => vec![root.as_ref().join(relative)],
+        Some(relative) => std::iter::once(root)
+            .chain(search_paths)
+            .map(|base| base.as_ref().join(&relative))
+            .collect(),
+        None => vec![PathBuf::from(path)],
+    }
+}
+
+fn candidates(base: &Path) -> Vec<PathBuf> {
+    let mut candidates = vec![base.to_path_buf()];
+    if base.extension().is_none() {
+        candidates.push(base.with_extension(EXTENSION));
+    }
+
+    candidates.push(base.join(INDEX));
+    candidates
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct FileSystemLoader;
+
+impl FileSystemLoader {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ModuleLoader for FileSystemLoader {
+    fn resolve(
+        &self,
+        path: &str,
+        root: &CanonicalPath,
+        search_paths: &[CanonicalPath],
+    ) -> Option<CanonicalPath> {
+        bases(path, root, search_paths)
+            .iter()
+            .flat_map(|base| candidates(base))
+            .filter(|candidate| candidate.is_file())
+            .find_map(|candidate| CanonicalPath::from(&candidate))
+    }
+
+    fn load(&self, path: &CanonicalPath) -> Option<String> {
+        path.read().ok()
+    }
+
+    fn uses_filesystem(&self) -> bool {
+        true
+    }
+
+    fn fork(&self) -> Option<Box<dyn ModuleLoader + Send>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct MemoryLoader {
+    sources: HashMap<CanonicalPath, String>,
+}
+
+impl MemoryLoader {
+    pub fn new() -> Self {
+        Self {
+            sources: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, path: &str, source: String) {
+        self.sources
+            .insert(CanonicalPath::from_virtual(path), source);
+    }
+
+    pub fn with(mut self, path: &str, source: String) -> Self {
+        self.add(path, source);
+        self
+    }
+}
+
+impl ModuleLoader for MemoryLoader {
+    fn resolve(
+        &self,
+        path: &str,
+        root: &CanonicalPath,
+        search_paths: &[CanonicalPath],
+    ) -> Option<CanonicalPath> {
+        // Memory loader paths are virtual, so modules that aren't relative to the importing module
+        // can also be found by their path from the root of the loader.
+        let mut bases = bases(path, root, search_paths);
+        if !is_explicitly_relative(path) {
+            bases.push(PathBuf::from(path));
+        }
+
+        bases
+            .iter()
+            .flat_map(|base| candidates(base))
+            .map(CanonicalPath::from_virtual)
+            .find(|candidate| self.sources.contains_key(candidate))
+    }
+
+    fn load(&self, path: &CanonicalPath) -> Option<String> {
+        self.sources.get(path).cloned()
+    }
+
+    fn fork(&self) -> Option<Box<dyn ModuleLoader + Send>> {
+        Some(Box::new(self.clone()))
+    }
+}
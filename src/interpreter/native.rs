@@ -1,35 +1,72 @@
+use std::fmt::{Debug, Formatter, Result as FormatResult};
+
 use crate::error::RegisError;
-use crate::shared::SharedImmutable;
+use crate::shared::{MaybeSend, SharedImmutable};
 
+use super::convert::TypedCallback;
+use super::function::{Function, ProcedureVariant};
+use super::object::Object;
 use super::value::Value;
 use super::Interpreter;
 
+#[cfg(feature = "sync")]
+use super::convert::IntoValue;
+#[cfg(feature = "sync")]
+use super::worker::Message;
+
 pub type ExternalProcedureCallback =
     fn(arguments: &[Value], context: &mut ExternalCallContext) -> Result<Value, RegisError>;
 
+#[cfg(not(feature = "sync"))]
+pub type ExternalProcedureClosure =
+    dyn Fn(&[Value], &mut ExternalCallContext) -> Result<Value, RegisError>;
+
+#[cfg(feature = "sync")]
+pub type ExternalProcedureClosure =
+    dyn Fn(&[Value], &mut ExternalCallContext) -> Result<Value, RegisError> + Send + Sync;
+
 pub struct ExternalCallContext<'interpreter> {
     pub interpreter: &'interpreter mut Interpreter,
 }
 
+impl<'interpreter> ExternalCallContext<'interpreter> {
+    pub fn call(&mut self, target: &Value, arguments: &[Value]) -> Result<Value, RegisError> {
+        self.interpreter.call(target, arguments)
+    }
+}
+
+#[derive(Clone)]
 pub struct ExternalProcedure {
     name: SharedImmutable<String>,
     arity: usize,
-    callback: ExternalProcedureCallback,
+    callback: SharedImmutable<Box<ExternalProcedureClosure>>,
 }
 
 impl ExternalProcedure {
-    pub fn new(
-        name: SharedImmutable<String>,
-        arity: usize,
-        callback: ExternalProcedureCallback,
-    ) -> Self {
+    pub fn new<F>(name: SharedImmutable<String>, arity: usize, callback: F) -> Self
+    where
+        F: Fn(&[Value], &mut ExternalCallContext) -> Result<Value, RegisError>
+            + MaybeSend
+            + 'static,
+    {
         Self {
             name,
             arity,
-            callback,
+            callback: SharedImmutable::new(Box::new(callback)),
         }
     }
 
+    pub fn from_typed<F, A>(name: SharedImmutable<String>, callback: F) -> Self
+    where
+        F: TypedCallback<A> + MaybeSend + 'static,
+    {
+        let arity = F::arity();
+        let function_name = name.clone();
+        Self::new(name, arity, move |arguments, context| {
+            callback.call(&function_name, arguments, context)
+        })
+    }
+
     pub fn name(&self) -> &SharedImmutable<String> {
         &self.name
     }
@@ -43,6 +80,115 @@ impl ExternalProcedure {
         arguments: &[Value],
         context: &mut ExternalCallContext,
     ) -> Result<Value, RegisError> {
-        (self.callback)(arguments, context)
+        (*self.callback)(arguments, context)
+    }
+}
+
+#[derive(Clone)]
+enum NativeModuleMember {
+    Value(Value),
+    // Values copied from another interpreter, see NativeModule::fork.
+    #[cfg(feature = "sync")]
+    Message(Message),
+    Procedure(ExternalProcedure),
+}
+
+#[derive(Default, Clone)]
+pub struct NativeModule {
+    members: Vec<(SharedImmutable<String>, NativeModuleMember)>,
+}
+
+impl Debug for NativeModule {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        formatter
+            .debug_list()
+            .entries(self.members.iter().map(|(name, _)| name))
+            .finish()
+    }
+}
+
+impl NativeModule {
+    pub fn new() -> Self {
+        Self {
+            members: Vec::new(),
+        }
+    }
+
+    pub fn with_value(mut self, name: &str, value: Value) -> Self {
+        self.members
+            .push((name.into(), NativeModuleMember::Value(value)));
+        self
+    }
+
+    pub fn with_function<F>(mut self, name: &str, arity: usize, callback: F) -> Self
+    where
+        F: Fn(&[Value], &mut ExternalCallContext) -> Result<Value, RegisError>
+            + MaybeSend
+            + 'static,
+    {
+        let procedure = ExternalProcedure::new(name.into(), arity, callback);
+        self.members
+            .push((name.into(), NativeModuleMember::Procedure(procedure)));
+        self
+    }
+
+    pub fn with_typed_function<F, A>(mut self, name: &str, callback: F) -> Self
+    where
+        F: TypedCallback<A> + MaybeSend + 'static,
+    {
+        let procedure = ExternalProcedure::from_typed(name.into(), callback);
+        self.members
+            .push((name.into(), NativeModuleMember::Procedure(procedure)));
+        self
+    }
+
+    pub fn build(self, interpreter: &mut Interpreter) -> Object {
+        let mut exports = Object::new(interpreter.generate_id());
+        exports.reserve(self.members.len());
+
+        for (name, member) in self.members {
+            let value = match member {
+                NativeModuleMember::Value(value) => value,
+                #[cfg(feature = "sync")]
+                NativeModuleMember::Message(message) => message.into_value(interpreter),
+                NativeModuleMember::Procedure(procedure) => {
+                    let function = Function::new(
+                        interpreter.generate_id(),
+                        ProcedureVariant::External(procedure.into()),
+                    );
+                    interpreter.track(Value::Function(function.into()))
+                }
+            };
+
+            exports.set(Value::String(name), value);
+        }
+
+        exports
+    }
+
+    // A copy of the module that can be registered in a worker's interpreter. Functions are shared,
+    // but values belong to the interpreter they were created in, so they're copied.
+    #[cfg(feature = "sync")]
+    pub(super) fn fork(&self) -> Result<Self, RegisError> {
+        let members = self
+            .members
+            .iter()
+            .map(|(name, member)| {
+                let member = match member {
+                    NativeModuleMember::Value(value) => {
+                        NativeModuleMember::Message(Message::from_value(value)?)
+                    }
+                    NativeModuleMember::Message(message) => {
+                        NativeModuleMember::Message(message.clone())
+                    }
+                    NativeModuleMember::Procedure(procedure) => {
+                        NativeModuleMember::Procedure(procedure.clone())
+                    }
+                };
+                Ok((name.clone(), member))
+            })
+            .collect::<Result<_, RegisError>>()?;
+
+        Ok(Self { members })
     }
 }
@@ -77,6 +77,14 @@ impl Object {
         self.inner.insert(index, value);
     }
 
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Value, &Value)> {
+        self.inner.iter()
+    }
+
     pub fn reserve(&mut self, capacity: usize) {
         self.inner.reserve(capacity);
     }
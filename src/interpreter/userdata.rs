@@ -0,0 +1,228 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter, Result as FormatResult};
+use std::hash::{Hash, Hasher};
+
+use crate::error::RegisError;
+use crate::shared::{MappedRef, MappedRefMut, MaybeSend, SharedImmutable, SharedMutable};
+
+use super::native::ExternalCallContext;
+use super::rid::Rid;
+use super::value::{Value, ValueType};
+
+#[cfg(not(feature = "sync"))]
+pub type UserDataMethodCallback =
+    dyn Fn(&UserData, &[Value], &mut ExternalCallContext) -> Result<Value, RegisError>;
+
+#[cfg(not(feature = "sync"))]
+pub type UserDataGetIndexCallback =
+    dyn Fn(&UserData, &Value, &mut ExternalCallContext) -> Result<Value, RegisError>;
+
+#[cfg(not(feature = "sync"))]
+pub type UserDataSetIndexCallback =
+    dyn Fn(&UserData, Value, Value, &mut ExternalCallContext) -> Result<(), RegisError>;
+
+#[cfg(not(feature = "sync"))]
+type UserDataValue = dyn Any;
+
+#[cfg(feature = "sync")]
+pub type UserDataMethodCallback = dyn Fn(&UserData, &[Value], &mut ExternalCallContext) -> Result<Value, RegisError>
+    + Send
+    + Sync;
+
+#[cfg(feature = "sync")]
+pub type UserDataGetIndexCallback =
+    dyn Fn(&UserData, &Value, &mut ExternalCallContext) -> Result<Value, RegisError> + Send + Sync;
+
+#[cfg(feature = "sync")]
+pub type UserDataSetIndexCallback = dyn Fn(&UserData, Value, Value, &mut ExternalCallContext) -> Result<(), RegisError>
+    + Send
+    + Sync;
+
+#[cfg(feature = "sync")]
+type UserDataValue = dyn Any + Send + Sync;
+
+pub struct UserDataMethod {
+    arity: usize,
+    callback: Box<UserDataMethodCallback>,
+}
+
+impl UserDataMethod {
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    pub fn call(
+        &self,
+        this: &UserData,
+        arguments: &[Value],
+        context: &mut ExternalCallContext,
+    ) -> Result<Value, RegisError> {
+        (self.callback)(this, arguments, context)
+    }
+}
+
+pub struct UserDataClass {
+    name: SharedImmutable<String>,
+    methods: HashMap<SharedImmutable<String>, SharedImmutable<UserDataMethod>>,
+    get_index: Option<Box<UserDataGetIndexCallback>>,
+    set_index: Option<Box<UserDataSetIndexCallback>>,
+    call: Option<(usize, Box<UserDataMethodCallback>)>,
+}
+
+impl UserDataClass {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            methods: HashMap::new(),
+            get_index: None,
+            set_index: None,
+            call: None,
+        }
+    }
+
+    pub fn with_method<F>(mut self, name: &str, arity: usize, callback: F) -> Self
+    where
+        F: Fn(&UserData, &[Value], &mut ExternalCallContext) -> Result<Value, RegisError>
+            + MaybeSend
+            + 'static,
+    {
+        let method = UserDataMethod {
+            arity,
+            callback: Box::new(callback),
+        };
+
+        self.methods.insert(name.into(), method.into());
+        self
+    }
+
+    pub fn with_get_index<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&UserData, &Value, &mut ExternalCallContext) -> Result<Value, RegisError>
+            + MaybeSend
+            + 'static,
+    {
+        self.get_index = Some(Box::new(callback));
+        self
+    }
+
+    pub fn with_set_index<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&UserData, Value, Value, &mut ExternalCallContext) -> Result<(), RegisError>
+            + MaybeSend
+            + 'static,
+    {
+        self.set_index = Some(Box::new(callback));
+        self
+    }
+
+    pub fn with_call<F>(mut self, arity: usize, callback: F) -> Self
+    where
+        F: Fn(&UserData, &[Value], &mut ExternalCallContext) -> Result<Value, RegisError>
+            + MaybeSend
+            + 'static,
+    {
+        self.call = Some((arity, Box::new(callback)));
+        self
+    }
+
+    pub fn name(&self) -> &SharedImmutable<String> {
+        &self.name
+    }
+
+    pub fn method(
+        &self,
+        name: &SharedImmutable<String>,
+    ) -> Option<&SharedImmutable<UserDataMethod>> {
+        self.methods.get(name)
+    }
+
+    pub fn get_index(&self) -> Option<&UserDataGetIndexCallback> {
+        self.get_index.as_deref()
+    }
+
+    pub fn set_index(&self) -> Option<&UserDataSetIndexCallback> {
+        self.set_index.as_deref()
+    }
+
+    pub fn call(&self) -> Option<(usize, &UserDataMethodCallback)> {
+        self.call
+            .as_ref()
+            .map(|(arity, callback)| (*arity, callback.as_ref()))
+    }
+}
+
+pub struct UserData {
+    id: Rid,
+    class: SharedImmutable<UserDataClass>,
+    inner: SharedMutable<Box<UserDataValue>>,
+}
+
+impl PartialEq for UserData {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for UserData {}
+
+impl Hash for UserData {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl Display for UserData {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "<userdata:{}>", *self.class.name())
+    }
+}
+
+impl Debug for UserData {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        write!(formatter, "<userdata:{}>", *self.class.name())
+    }
+}
+
+impl UserData {
+    pub fn new<T: Any + MaybeSend>(
+        id: Rid,
+        class: SharedImmutable<UserDataClass>,
+        value: T,
+    ) -> Self {
+        let value: Box<UserDataValue> = Box::new(value);
+        Self {
+            id,
+            class,
+            inner: value.into(),
+        }
+    }
+
+    pub fn id(&self) -> Rid {
+        self.id
+    }
+
+    pub fn type_of(&self) -> ValueType {
+        ValueType::UserData(self.class.name().clone())
+    }
+
+    pub fn to_boolean(&self) -> bool {
+        true
+    }
+
+    pub fn class(&self) -> &SharedImmutable<UserDataClass> {
+        &self.class
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.inner.borrow().is::<T>()
+    }
+
+    pub fn borrow<T: Any>(&self) -> Option<MappedRef<'_, T>> {
+        self.inner.borrow_map(|inner| inner.downcast_ref::<T>())
+    }
+
+    pub fn borrow_mut<T: Any>(&self) -> Option<MappedRefMut<'_, T>> {
+        self.inner.borrow_mut_map(|inner| inner.downcast_mut::<T>())
+    }
+}
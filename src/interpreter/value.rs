@@ -6,6 +6,8 @@ use crate::shared::{SharedImmutable, SharedMutable};
 use super::function::Function;
 use super::list::List;
 use super::object::Object;
+use super::userdata::UserData;
+use super::worker::Worker;
 
 #[derive(Debug)]
 pub enum Value {
@@ -17,6 +19,8 @@ pub enum Value {
     List(SharedMutable<List>),
     Object(SharedMutable<Object>),
     Function(SharedImmutable<Function>),
+    UserData(SharedImmutable<UserData>),
+    Worker(SharedImmutable<Worker>),
 }
 
 impl Clone for Value {
@@ -30,6 +34,8 @@ impl Clone for Value {
             Self::List(value) => Self::List(value.clone()),
             Self::Object(value) => Self::Object(value.clone()),
             Self::Function(value) => Self::Function(value.clone()),
+            Self::UserData(value) => Self::UserData(value.clone()),
+            Self::Worker(value) => Self::Worker(value.clone()),
         }
     }
 }
@@ -47,6 +53,8 @@ impl PartialEq for Value {
             (Self::List(left), Self::List(right)) => left == right,
             (Self::Object(left), Self::Object(right)) => left == right,
             (Self::Function(left), Self::Function(right)) => left == right,
+            (Self::UserData(left), Self::UserData(right)) => left == right,
+            (Self::Worker(left), Self::Worker(right)) => left == right,
             _ => false,
         }
     }
@@ -65,6 +73,8 @@ impl Hash for Value {
             Self::List(value) => value.hash(state),
             Self::Object(value) => value.hash(state),
             Self::Function(value) => value.hash(state),
+            Self::UserData(value) => value.hash(state),
+            Self::Worker(value) => value.hash(state),
         };
     }
 }
@@ -83,6 +93,8 @@ impl Display for Value {
                 Self::List(value) => value.borrow().to_string(),
                 Self::Object(value) => value.borrow().to_string(),
                 Self::Function(value) => value.to_string(),
+                Self::UserData(value) => value.to_string(),
+                Self::Worker(value) => value.to_string(),
             }
         )
     }
@@ -99,6 +111,8 @@ impl Value {
             Self::List(value) => value.borrow().type_of(),
             Self::Object(value) => value.borrow().type_of(),
             Self::Function(value) => value.type_of(),
+            Self::UserData(value) => value.type_of(),
+            Self::Worker(..) => ValueType::Worker,
         }
     }
 
@@ -112,6 +126,8 @@ impl Value {
             Self::List(value) => value.borrow().to_boolean(),
             Self::Object(value) => value.borrow().to_boolean(),
             Self::Function(value) => value.to_boolean(),
+            Self::UserData(value) => value.to_boolean(),
+            Self::Worker(..) => true,
         }
     }
 }
@@ -126,6 +142,8 @@ pub enum ValueType {
     List,
     Object,
     Function,
+    Worker,
+    UserData(SharedImmutable<String>),
 }
 
 impl Display for ValueType {
@@ -139,6 +157,8 @@ impl Display for ValueType {
             Self::List => write!(formatter, "list"),
             Self::Object => write!(formatter, "object"),
             Self::Function => write!(formatter, "function"),
+            Self::Worker => write!(formatter, "worker"),
+            Self::UserData(name) => write!(formatter, "{}", name),
         }
     }
 }
@@ -0,0 +1,293 @@
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::hash::{Hash, Hasher};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::thread::{Builder, JoinHandle};
+
+use crate::error::{RegisError, RegisErrorVariant};
+use crate::source::CanonicalPath;
+
+use super::convert::IntoValue;
+use super::interrupt::{InterruptHandle, POLL_INTERVAL};
+use super::list::List;
+use super::object::Object;
+use super::rid::Rid;
+use super::value::Value;
+use super::{FileSystemLoader, Interpreter};
+
+// A deep copy of a value that doesn't share anything with the interpreter it came from, so it can
+// be sent to another thread. Only plain data can be copied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Null,
+    Boolean(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<Message>),
+    Object(Vec<(Message, Message)>),
+}
+
+impl Message {
+    pub fn from_value(value: &Value) -> Result<Self, RegisError> {
+        Self::copy(value, &mut Vec::new())
+    }
+
+    fn copy(value: &Value, parents: &mut Vec<usize>) -> Result<Self, RegisError> {
+        // Values that are reachable more than once are copied each time, but a value that contains
+        // itself can't be copied at all.
+        let address = match value {
+            Value::List(list) => Some(list.address()),
+            Value::Object(object) => Some(object.address()),
+            _ => None,
+        };
+
+        if let Some(address) = address {
+            if parents.contains(&address) {
+                return Err(message_error(
+                    "Cannot send a value that contains itself.".into(),
+                ));
+            }
+
+            parents.push(address);
+        }
+
+        let result = match value {
+            Value::Null => Message::Null,
+            Value::Boolean(value) => Message::Boolean(*value),
+            Value::Int(value) => Message::Int(*value),
+            Value::Float(value) => Message::Float(*value),
+            Value::String(value) => Message::String((**value).clone()),
+            Value::List(list) => Message::List(
+                list.borrow()
+                    .iter()
+                    .map(|value| Self::copy(value, parents))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Object(object) => Message::Object(
+                object
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| {
+                        Ok((Self::copy(key, parents)?, Self::copy(value, parents)?))
+                    })
+                    .collect::<Result<_, _>>()?,
+            ),
+            Value::Function(..) | Value::UserData(..) | Value::Worker(..) => {
+                return Err(message_error(format!(
+                    "Cannot send a value of type '{}' to a worker.",
+                    value.type_of()
+                )))
+            }
+        };
+
+        if address.is_some() {
+            parents.pop();
+        }
+
+        Ok(result)
+    }
+}
+
+impl IntoValue for Message {
+    fn into_value(self, interpreter: &mut Interpreter) -> Value {
+        match self {
+            Message::Null => Value::Null,
+            Message::Boolean(value) => Value::Boolean(value),
+            Message::Int(value) => Value::Int(value),
+            Message::Float(value) => Value::Float(value),
+            Message::String(value) => Value::String(value.into()),
+            Message::List(values) => {
+                let mut list = List::new(interpreter.generate_id());
+                list.reserve(values.len());
+                for value in values {
+                    list.push(value.into_value(interpreter));
+                }
+
+                interpreter.track(Value::List(list.into()))
+            }
+            Message::Object(values) => {
+                let mut object = Object::new(interpreter.generate_id());
+                object.reserve(values.len());
+                for (key, value) in values {
+                    let key = key.into_value(interpreter);
+                    let value = value.into_value(interpreter);
+                    object.set(key, value);
+                }
+
+                interpreter.track(Value::Object(object.into()))
+            }
+        }
+    }
+}
+
+// One end of the channels between an interpreter and a worker it started. The interpreter holds
+// the end for the worker's thread, and the worker gets the other end as @parent. Once the
+// interpreter drops its end, the worker is interrupted and its thread joined.
+#[derive(Debug)]
+pub struct Worker {
+    id: Rid,
+    path: Option<CanonicalPath>,
+    sender: Sender<Message>,
+    receiver: Mutex<Receiver<Message>>,
+    thread: Mutex<Option<JoinHandle<Result<(), String>>>>,
+    interrupt: Option<InterruptHandle>,
+}
+
+impl Worker {
+    pub fn spawn(interpreter: &mut Interpreter, path: CanonicalPath) -> Result<Self, RegisError> {
+        let loader = interpreter.loader.fork().ok_or_else(|| {
+            worker_error("The module loader does not support starting workers.".into())
+        })?;
+        let config = interpreter.config.clone();
+        let search_paths = interpreter.search_paths.clone();
+        let packages = interpreter.packages.clone();
+        let handlers = interpreter.handlers.clone();
+        let interrupt = interpreter.interrupt.child();
+        let worker_interrupt = interrupt.clone();
+
+        // Without the sync feature the output and native modules can't be sent to another thread,
+        // so workers print to stdout and can only import the built-in modules.
+        #[cfg(feature = "sync")]
+        let output = interpreter.output.clone();
+        #[cfg(feature = "sync")]
+        let native_modules = interpreter
+            .native_modules
+            .iter()
+            .map(|(name, module)| Ok((name.clone(), module.fork()?)))
+            .collect::<Result<Vec<_>, RegisError>>()?;
+
+        let (parent_sender, worker_receiver) = channel();
+        let (worker_sender, parent_receiver) = channel();
+
+        let main = path.clone();
+        let thread = Builder::new()
+            .name(format!("worker:{}", path))
+            .spawn(move || {
+                // The worker's interpreter is created on its own thread, so nothing it allocates is
+                // ever shared with the interpreter that started it.
+                let mut worker =
+                    Interpreter::with_config(main.clone(), FileSystemLoader::new(), config);
+                worker.loader = loader;
+                worker.search_paths = search_paths;
+                worker.packages = packages;
+                worker.handlers = handlers;
+                worker.interrupt = worker_interrupt;
+                #[cfg(feature = "sync")]
+                {
+                    worker.output = output;
+                    for (name, module) in native_modules {
+                        worker.register_native_module(&name, module);
+                    }
+                }
+
+                let parent = Worker {
+                    id: worker.generate_id(),
+                    path: None,
+                    sender: worker_sender,
+                    receiver: Mutex::new(worker_receiver),
+                    thread: Mutex::new(None),
+                    interrupt: None,
+                };
+                worker.add_global("@parent".into(), Value::Worker(parent.into()));
+
+                worker.load_module(&main).map_err(|error| {
+                    let source = error
+                        .location()
+                        .as_ref()
+                        .and_then(|location| location.path().as_ref())
+                        .and_then(|path| worker.loader().load(path));
+                    error.show(source.as_deref())
+                })
+            })
+            .map_err(|error| worker_error(format!("Could not start worker. {}", error)))?;
+
+        Ok(Self {
+            id: interpreter.generate_id(),
+            path: Some(path),
+            sender: parent_sender,
+            receiver: Mutex::new(parent_receiver),
+            thread: Mutex::new(Some(thread)),
+            interrupt: Some(interrupt),
+        })
+    }
+
+    // Returns false if the other end is gone, e.g. because the worker has finished.
+    pub fn send(&self, message: Message) -> bool {
+        self.sender.send(message).is_ok()
+    }
+
+    // Blocks until a message arrives or the interpreter waiting for it is interrupted. Returns None
+    // once the other end is gone and every message it sent has been received, or an error if the
+    // worker failed.
+    pub fn recv(&self, interrupt: &InterruptHandle) -> Result<Option<Message>, RegisError> {
+        let receiver = self.receiver.lock().unwrap();
+        loop {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(message) => return Ok(Some(message)),
+                Err(RecvTimeoutError::Timeout) => interrupt.check()?,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        // A worker stops when the interpreter waiting for it is interrupted, which is reported
+        // instead of the worker's error.
+        drop(receiver);
+        interrupt.check()?;
+        self.join()?;
+        Ok(None)
+    }
+
+    fn join(&self) -> Result<(), RegisError> {
+        let thread = match self.thread.lock().unwrap().take() {
+            Some(thread) => thread,
+            None => return Ok(()),
+        };
+
+        match thread.join() {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(message)) => Err(worker_error(format!("{} failed.\n{}", self, message))),
+            Err(..) => Err(worker_error(format!("{} panicked.", self))),
+        }
+    }
+}
+
+impl Drop for Worker {
+    fn drop(&mut self) {
+        if let Some(interrupt) = &self.interrupt {
+            interrupt.interrupt();
+        }
+
+        // Nothing is left to receive the worker's error, so it's ignored.
+        let _ = self.join();
+    }
+}
+
+impl Display for Worker {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match &self.path {
+            Some(path) => write!(formatter, "<worker:{}>", path),
+            None => write!(formatter, "<worker:parent>"),
+        }
+    }
+}
+
+impl Hash for Worker {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state)
+    }
+}
+
+impl PartialEq for Worker {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+fn message_error(message: String) -> RegisError {
+    RegisError::new(None, RegisErrorVariant::TypeError { message })
+}
+
+fn worker_error(message: String) -> RegisError {
+    RegisError::new(None, RegisErrorVariant::WorkerError { message })
+}
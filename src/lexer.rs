@@ -129,6 +129,7 @@ impl<'source> Lexer<'source> {
             "let" => Keyword::Let,
             "fn" => Keyword::Fn,
             "export" => Keyword::Export,
+            "import" => Keyword::Import,
             "if" => Keyword::If,
             "else" => Keyword::Else,
             "while" => Keyword::While,
@@ -404,6 +405,12 @@ mod tests {
             .token(4, TokenKind::Keyword(Export), Export.text())
             .count(5);
 
+        Check::new("import from as")
+            .token(0, TokenKind::Keyword(Import), Import.text())
+            .token(2, TokenKind::Ident, "from")
+            .token(4, TokenKind::Ident, "as")
+            .count(5);
+
         Check::new("if else while loop")
             .token(0, TokenKind::Keyword(If), If.text())
             .token(2, TokenKind::Keyword(Else), Else.text())
@@ -46,6 +46,7 @@ pub enum Keyword {
     Let,
     Fn,
     Export,
+    Import,
     If,
     Else,
     While,
@@ -67,6 +68,7 @@ impl Keyword {
             Keyword::Let => "let",
             Keyword::Fn => "fn",
             Keyword::Export => "export",
+            Keyword::Import => "import",
             Keyword::If => "if",
             Keyword::Else => "else",
             Keyword::While => "while",
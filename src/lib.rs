@@ -1,8 +1,10 @@
 pub mod ast;
 pub mod bytecode;
 pub mod error;
+pub mod formatter;
 pub mod interpreter;
 pub mod lexer;
+pub mod manifest;
 pub mod parser;
 pub mod shared;
 pub mod source;
@@ -1,32 +1,244 @@
+use std::collections::HashMap;
+use std::fs;
+use std::thread;
+use std::time::{Duration, SystemTime};
 use std::{env, process};
 
+use regis::ast::{dump_json, dump_tree, Node};
+use regis::bytecode::Disassembler;
+use regis::error::{RegisError, RegisErrorVariant};
+use regis::formatter::Formatter;
 use regis::interpreter::Interpreter;
-use regis::source::CanonicalPath;
+use regis::manifest::Project;
+use regis::source::{CanonicalPath, Location};
+
+enum AstFormat {
+    Tree,
+    Json,
+}
 
 fn main() {
-    let args = env::args().collect::<Vec<_>>();
-    let first = args.get(1).unwrap_or_else(|| {
-        println!("ERROR: Provide a file to execute.");
-        process::exit(1);
-    });
-    let path = CanonicalPath::from(first).unwrap_or_else(|| {
-        println!("ERROR: Specified file path does not exist.");
-        process::exit(1);
-    });
+    let mut command = None;
+    let mut files = Vec::new();
+    let mut search_paths = Vec::new();
+    let mut watch = false;
+    let mut dump_bytecode = false;
+    let mut dump_ast = None;
+    let mut check = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--path" | "-p" => {
+                let search_path = args.next().unwrap_or_else(|| {
+                    println!("ERROR: Provide a directory after '{}'.", arg);
+                    process::exit(1);
+                });
+                search_paths.push(CanonicalPath::from(&search_path).unwrap_or_else(|| {
+                    println!("ERROR: Search path '{}' does not exist.", search_path);
+                    process::exit(1);
+                }));
+            }
+            "--watch" | "-w" => watch = true,
+            "--dump-bytecode" => dump_bytecode = true,
+            "--dump-ast" | "--dump-ast=tree" => dump_ast = Some(AstFormat::Tree),
+            "--dump-ast=json" => dump_ast = Some(AstFormat::Json),
+            "--check" => check = true,
+            "run" | "fmt" if command.is_none() && files.is_empty() => command = Some(arg),
+            // Any number of files can be formatted at once.
+            _ if files.is_empty() || command.as_deref() == Some("fmt") => files.push(arg),
+            _ => {
+                println!("ERROR: Unexpected argument '{}'.", arg);
+                process::exit(1);
+            }
+        }
+    }
+
+    if command.as_deref() == Some("fmt") {
+        if files.is_empty() {
+            println!("ERROR: Provide a file to format.");
+            process::exit(1);
+        }
+
+        if !format_files(&files, check) {
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    // Search paths from the environment are used after any given on the command line. Ones that
+    // don't exist are ignored.
+    if let Some(paths) = env::var_os("REGIS_PATH") {
+        search_paths.extend(env::split_paths(&paths).filter_map(|path| CanonicalPath::from(&path)));
+    }
+
+    // Look for a project manifest in the working directory or any of its ancestors.
+    let project = env::current_dir()
+        .ok()
+        .and_then(|directory| Project::find(&directory))
+        .map(|manifest| {
+            Project::load(&manifest).unwrap_or_else(|error| {
+                println!("ERROR: {}", error);
+                process::exit(1);
+            })
+        });
+
+    let path = match (command.as_deref(), files.first(), &project) {
+        (Some("run"), None, Some(project)) => project.entry().clone(),
+        (Some("run"), None, None) => {
+            println!("ERROR: No regis.toml found in this directory or any of its parents.");
+            process::exit(1);
+        }
+        (_, Some(file), _) => CanonicalPath::from(file).unwrap_or_else(|| {
+            println!("ERROR: Specified file path does not exist.");
+            process::exit(1);
+        }),
+        (_, None, _) => {
+            println!("ERROR: Provide a file to execute.");
+            process::exit(1);
+        }
+    };
 
     let mut interpreter = Interpreter::new(path.clone());
+    for search_path in search_paths {
+        interpreter.add_search_path(search_path);
+    }
+
+    if let Some(project) = &project {
+        project.configure(&mut interpreter);
+    }
+
+    if let Some(format) = dump_ast {
+        match interpreter.parse_module(&path) {
+            Ok(chunk) => match format {
+                AstFormat::Tree => print!("{}", dump_tree(Node::Chunk(&chunk))),
+                AstFormat::Json => println!("{:#}", dump_json(Node::Chunk(&chunk))),
+            },
+            Err(error) => {
+                report_error(&interpreter, &error);
+                process::exit(1);
+            }
+        }
+
+        return;
+    }
+
+    if dump_bytecode {
+        match interpreter.compile_module(&path) {
+            Ok(module) => {
+                let source = interpreter.loader().load(&path).unwrap();
+                print!("{}", Disassembler::new(&source).disassemble_module(&module));
+            }
+            Err(error) => {
+                report_error(&interpreter, &error);
+                process::exit(1);
+            }
+        }
+
+        return;
+    }
+
     if let Err(error) = interpreter.load_module(&path) {
-        if let Some(source) = error
-            .location()
-            .as_ref()
-            .and_then(|location| location.path().as_ref())
-            .and_then(|path| path.read().ok())
-        {
-            println!("{}", error.show(Some(&source)));
+        report_error(&interpreter, &error);
+        if !watch {
+            process::exit(1);
+        }
+    }
+
+    if watch {
+        watch_modules(&mut interpreter, &path);
+    }
+}
+
+// Formats each file in place, or with --check only reports the ones that aren't formatted. Returns
+// false if any file couldn't be formatted or, when checking, isn't formatted.
+fn format_files(files: &[String], check: bool) -> bool {
+    let mut success = true;
+    for file in files {
+        let path = CanonicalPath::from(file).unwrap_or_else(|| {
+            println!("ERROR: Specified file path '{}' does not exist.", file);
+            process::exit(1);
+        });
+        let source = path.read().unwrap_or_else(|error| {
+            println!("ERROR: Could not read '{}'. {}", path, error);
+            process::exit(1);
+        });
+
+        let formatted = match Formatter::new(&source).format() {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                let error = RegisError::new(
+                    Some(Location::new(Some(path.clone()), *error.span())),
+                    RegisErrorVariant::ParseError {
+                        message: error.to_string(),
+                    },
+                );
+                println!("{}", error.show(Some(&source)));
+                success = false;
+                continue;
+            }
+        };
+
+        if formatted == source {
+            continue;
+        }
+
+        if check {
+            println!("- unformatted -> {}", path);
+            success = false;
+        } else if let Err(error) = fs::write(&path, formatted) {
+            println!("ERROR: Could not write '{}'. {}", path, error);
+            success = false;
         } else {
-            println!("{}", error.show(None));
+            println!("- formatted -> {}", path);
+        }
+    }
+
+    success
+}
+
+fn report_error(interpreter: &Interpreter, error: &RegisError) {
+    if let Some(source) = error
+        .location()
+        .as_ref()
+        .and_then(|location| location.path().as_ref())
+        .and_then(|path| interpreter.loader().load(path))
+    {
+        println!("{}", error.show(Some(&source)));
+    } else {
+        println!("{}", error.show(None));
+    }
+}
+
+fn watch_modules(interpreter: &mut Interpreter, main: &CanonicalPath) {
+    fn modified(path: &CanonicalPath) -> Option<SystemTime> {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    let mut times = HashMap::new();
+    loop {
+        // The main module is watched even if it failed to load, so fixing it reruns it.
+        let mut paths = interpreter.module_paths().cloned().collect::<Vec<_>>();
+        if !paths.contains(main) {
+            paths.push(main.clone());
+        }
+
+        for path in paths {
+            let time = modified(&path);
+            match times.insert(path.clone(), time) {
+                Some(previous) if previous != time => {
+                    println!("- reloading -> {}", path);
+                    if let Err(error) = interpreter.reload_module(&path) {
+                        report_error(interpreter, &error);
+                    }
+                }
+                _ => {}
+            }
         }
 
-        process::exit(1);
+        thread::sleep(Duration::from_millis(500));
     }
 }
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter, Result as FormatResult};
+use std::fs;
+use std::io::Error as IOError;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use toml::de::Error as TomlError;
+
+use crate::interpreter::Interpreter;
+use crate::source::CanonicalPath;
+
+pub const MANIFEST_FILE_NAME: &str = "regis.toml";
+
+#[derive(Debug, Deserialize)]
+pub struct Manifest {
+    pub project: ProjectSection,
+    #[serde(default)]
+    pub dependencies: HashMap<String, Dependency>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectSection {
+    pub name: String,
+    pub entry: PathBuf,
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Dependency {
+    pub path: PathBuf,
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Read { path: PathBuf, error: IOError },
+    Parse { path: PathBuf, error: TomlError },
+    PathDoesNotExist { path: PathBuf },
+}
+
+impl Display for ManifestError {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match self {
+            ManifestError::Read { path, error } => {
+                write!(formatter, "Could not read '{}'. {}", path.display(), error)
+            }
+            ManifestError::Parse { path, error } => {
+                write!(
+                    formatter,
+                    "Invalid manifest '{}'. {}",
+                    path.display(),
+                    error
+                )
+            }
+            ManifestError::PathDoesNotExist { path } => write!(
+                formatter,
+                "Path '{}' in the manifest does not exist.",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl Error for ManifestError {}
+
+#[derive(Debug)]
+pub struct Project {
+    name: String,
+    directory: CanonicalPath,
+    entry: CanonicalPath,
+    roots: Vec<CanonicalPath>,
+    dependencies: Vec<(String, CanonicalPath)>,
+}
+
+impl Project {
+    pub fn find<P: AsRef<Path>>(start: &P) -> Option<PathBuf> {
+        start
+            .as_ref()
+            .ancestors()
+            .map(|directory| directory.join(MANIFEST_FILE_NAME))
+            .find(|path| path.is_file())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: &P) -> Result<Self, ManifestError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|error| ManifestError::Read {
+            path: path.to_path_buf(),
+            error,
+        })?;
+        let manifest =
+            toml::from_str::<Manifest>(&source).map_err(|error| ManifestError::Parse {
+                path: path.to_path_buf(),
+                error,
+            })?;
+
+        let directory = resolve(path.parent().unwrap_or_else(|| Path::new(".")), &".")?;
+
+        // Every path in the manifest is relative to the directory it's in. If no source roots are
+        // given, the project directory is used.
+        let entry = resolve(directory.as_ref(), &manifest.project.entry)?;
+        let roots = if manifest.project.roots.is_empty() {
+            vec![directory.clone()]
+        } else {
+            manifest
+                .project
+                .roots
+                .iter()
+                .map(|root| resolve(directory.as_ref(), root))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut dependencies = manifest
+            .dependencies
+            .into_iter()
+            .map(|(name, dependency)| Ok((name, resolve(directory.as_ref(), &dependency.path)?)))
+            .collect::<Result<Vec<_>, _>>()?;
+        dependencies.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(Self {
+            name: manifest.project.name,
+            directory,
+            entry,
+            roots,
+            dependencies,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn directory(&self) -> &CanonicalPath {
+        &self.directory
+    }
+
+    pub fn entry(&self) -> &CanonicalPath {
+        &self.entry
+    }
+
+    pub fn roots(&self) -> &[CanonicalPath] {
+        &self.roots
+    }
+
+    pub fn dependencies(&self) -> &[(String, CanonicalPath)] {
+        &self.dependencies
+    }
+
+    pub fn configure(&self, interpreter: &mut Interpreter) {
+        for root in &self.roots {
+            interpreter.add_search_path(root.clone());
+        }
+
+        for (name, path) in &self.dependencies {
+            interpreter.add_package(name, path.clone());
+        }
+    }
+}
+
+fn resolve<P: AsRef<Path>>(directory: &Path, path: &P) -> Result<CanonicalPath, ManifestError> {
+    let path = directory.join(path);
+    CanonicalPath::from(&path).ok_or(ManifestError::PathDoesNotExist { path })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use crate::interpreter::{Interpreter, Value};
+
+    use super::Project;
+
+    #[test]
+    fn project() {
+        let directory = env::temp_dir().join(format!("regis-manifest-{}", std::process::id()));
+        fs::create_dir_all(directory.join("src/nested")).unwrap();
+        fs::create_dir_all(directory.join("vendor/numbers")).unwrap();
+        fs::write(
+            directory.join("regis.toml"),
+            "[project]\nname = \"test\"\nentry = \"src/main.regis\"\nroots = [\"src\"]\n\n\
+            [dependencies]\nnumbers = { path = \"vendor/numbers\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("src/main.regis"),
+            "import { one } from \"numbers\"; import { two } from \"nested/two\"; \
+            export let three = one + two;",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("src/nested/two.regis"),
+            "export let two = 2;",
+        )
+        .unwrap();
+        fs::write(
+            directory.join("vendor/numbers/index.regis"),
+            "export let one = 1;",
+        )
+        .unwrap();
+
+        let manifest = Project::find(&directory.join("src/nested")).unwrap();
+        let project = Project::load(&manifest).unwrap();
+        assert_eq!(project.name(), "test");
+
+        let mut interpreter = Interpreter::new(project.entry().clone());
+        project.configure(&mut interpreter);
+        let result = interpreter
+            .load_module(project.entry())
+            .and_then(|_| interpreter.eval_source("main", "@import(\"main\").three;"));
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(result.unwrap(), Value::Int(3));
+    }
+}
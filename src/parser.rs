@@ -162,4 +162,48 @@ impl<'source> Parser<'source> {
         )
         .map(|_| ())
     }
+
+    // Words like `from` and `as` are only keywords inside import and export statements, so they
+    // are lexed as identifiers and can still be used as names everywhere else.
+    fn peek_contextual_keyword(&mut self, word: &str) -> bool {
+        matches!(self.peek(), Some(token) if *token.kind() == TokenKind::Ident && token.slice() == word)
+    }
+
+    fn eat_contextual_keyword(&mut self, word: &'static str) -> ParseResult<'source, ()> {
+        let token = expect_exact!(
+            self.next(),
+            TokenKind::Ident,
+            ParseErrorKind::ExpectedQuoted(word),
+            self.index(),
+        )?;
+
+        if token.slice() == word {
+            Ok(())
+        } else {
+            Err(ParseError::at_token(
+                ParseErrorKind::ExpectedQuoted(word),
+                &token,
+            ))
+        }
+    }
+}
+
+// The dumped AST without any spans, to compare the ASTs of source that's written differently.
+#[cfg(test)]
+fn shape(node: crate::ast::Node<'_>) -> serde_json::Value {
+    fn strip_spans(json: &mut serde_json::Value) {
+        if let Some(object) = json.as_object_mut() {
+            object.remove("span");
+            if let Some(children) = object
+                .get_mut("children")
+                .and_then(|children| children.as_array_mut())
+            {
+                children.iter_mut().for_each(strip_spans);
+            }
+        }
+    }
+
+    let mut json = crate::ast::dump_json(node);
+    strip_spans(&mut json);
+    json
 }
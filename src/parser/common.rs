@@ -10,7 +10,7 @@ impl<'source> Parser<'source> {
         let start = self.start_node();
         let mut stmts = Vec::new();
         while self.peek().is_some() {
-            stmts.push(self.eat_stmt()?);
+            stmts.push(self.eat_module_stmt()?);
         }
         Ok(Chunk {
             info: self.end_node(start),
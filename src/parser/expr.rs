@@ -480,7 +480,7 @@ impl<'source> Parser<'source> {
         })
     }
 
-    fn eat_string_expr(&mut self) -> ParseResult<StringExpr> {
+    pub(super) fn eat_string_expr(&mut self) -> ParseResult<StringExpr> {
         let start = self.start_node();
         let token = expect!(
             self.next(),
@@ -727,6 +727,7 @@ struct BinaryOperatorSegment {
 
 #[cfg(test)]
 mod tests {
+    use super::super::shape;
     use super::*;
 
     #[test]
@@ -1027,4 +1028,126 @@ mod tests {
             Ok(Expr::BinaryOperation(..))
         ));
     }
+
+    fn assert_round_trip(source: &str) {
+        let expr = Parser::new(source)
+            .eat_expr()
+            .unwrap_or_else(|error| panic!("{}", error));
+        let unparsed = expr.to_string();
+        let reparsed = Parser::new(&unparsed)
+            .eat_expr()
+            .unwrap_or_else(|error| panic!("{}: {}", unparsed, error));
+
+        assert_eq!(
+            shape(Node::from_expr(&expr)),
+            shape(Node::from_expr(&reparsed)),
+            "{}",
+            unparsed
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_round_trip("null");
+        assert_round_trip("[true, false, 100, 0.5, 1.0, 123456789.125, []]");
+        assert_round_trip(
+            "\"quote \\\" backslash \\\\ newline \\n tab \\t unicode \\u{1F600} \\u0041\"",
+        );
+        assert_round_trip("{ a: 1, \"b c\": { d: [] }, [1 + 2]: 3, }");
+        assert_round_trip("fn run(a, b,) { return a + b; }");
+        assert_round_trip("fn => fn(x) => x * 2");
+        assert_round_trip("fn { if a { b(); } else if c { loop { break; } } else { d = 1; } }");
+        assert_round_trip("(1 + 2) * (3)");
+        assert_round_trip("object.a[0].b(1, 2)(3)[\"c\"]");
+        assert_round_trip("fn {}() .a");
+        assert_round_trip("1 .a + 1.5.a");
+        assert_round_trip("not -~1 + - -2 - -x");
+        assert_round_trip("1 + 2 - 3 * 10 / null ?? 5 > 1000 == true and a or b << c >> d & e | f");
+        assert_round_trip("a - (b - c) - d");
+    }
+
+    #[test]
+    fn unparse_built_expr() {
+        fn info() -> NodeInfo {
+            NodeInfo::new(Span::at(0))
+        }
+
+        fn int(value: i64) -> Expr {
+            Expr::Int(
+                IntExpr {
+                    info: info(),
+                    value,
+                }
+                .into(),
+            )
+        }
+
+        fn binary(operator: BinaryOperator, left: Expr, right: Expr) -> Expr {
+            Expr::BinaryOperation(
+                BinaryOperationExpr {
+                    info: info(),
+                    operator,
+                    left,
+                    right,
+                }
+                .into(),
+            )
+        }
+
+        fn unary(operator: UnaryOperator, right: Expr) -> Expr {
+            Expr::UnaryOperation(
+                UnaryOperationExpr {
+                    info: info(),
+                    operator,
+                    right,
+                }
+                .into(),
+            )
+        }
+
+        // Parentheses are added where the parser would otherwise group the operations differently.
+        let expr = binary(
+            BinaryOperator::Mul,
+            binary(BinaryOperator::Add, int(1), int(2)),
+            binary(BinaryOperator::Div, int(3), int(4)),
+        );
+        assert_eq!(expr.to_string(), "(1 + 2) * (3 / 4)");
+
+        let expr = binary(
+            BinaryOperator::Sub,
+            int(1),
+            binary(BinaryOperator::Sub, int(2), int(3)),
+        );
+        assert_eq!(expr.to_string(), "1 - (2 - 3)");
+
+        let expr = unary(
+            UnaryOperator::Not,
+            binary(BinaryOperator::Eq, int(-1), int(i64::MIN)),
+        );
+        assert_eq!(expr.to_string(), "not (-1 == (-9223372036854775807 - 1))");
+
+        let expr = unary(UnaryOperator::Neg, int(-1));
+        assert_eq!(expr.to_string(), "- -1");
+
+        // Negative literals are only ever built in Rust, the parser reads them as negations.
+        let expr = int(-1);
+        assert_eq!(expr.to_string(), "-1");
+        assert!(matches!(
+            Parser::new(&expr.to_string()).eat_expr(),
+            Ok(Expr::UnaryOperation(..))
+        ));
+
+        let expr = Expr::Float(
+            FloatExpr {
+                info: info(),
+                value: 2.0,
+            }
+            .into(),
+        );
+        assert_eq!(expr.to_string(), "2.0");
+        assert!(matches!(
+            Parser::new(&expr.to_string()).eat_expr(),
+            Ok(Expr::Float(..))
+        ));
+    }
 }
@@ -6,6 +6,20 @@ use super::result::ParseResult;
 use super::Parser;
 
 impl<'source> Parser<'source> {
+    pub(super) fn eat_module_stmt(&mut self) -> ParseResult<Stmt> {
+        // Import statements are only allowed at the top level of a module so they can be
+        // resolved before the module is built.
+        match (self.peek_kind(), self.lookahead_kind(1)) {
+            (TokenKind::Keyword(Keyword::Import), _) => {
+                Ok(Stmt::Import(self.eat_import_stmt()?.into()))
+            }
+            (TokenKind::Keyword(Keyword::Export), TokenKind::Symbol(Symbol::OpenBrace)) => {
+                Ok(Stmt::ExportFrom(self.eat_export_from_stmt()?.into()))
+            }
+            _ => self.eat_stmt(),
+        }
+    }
+
     pub fn eat_stmt(&mut self) -> ParseResult<Stmt> {
         let first = self.peek().cloned();
         let second = self.lookahead(1).cloned();
@@ -32,6 +46,15 @@ impl<'source> Parser<'source> {
             Some(TokenKind::Keyword(Keyword::Let)) => {
                 Stmt::VariableDeclaration(self.eat_variable_declaration_stmt()?.into())
             }
+            Some(TokenKind::Keyword(Keyword::Import)) => {
+                return Err(ParseError::at_token_or_index(
+                    ParseErrorKind::Specific(
+                        "Import statements are only allowed at the top level of a module.",
+                    ),
+                    first.as_ref(),
+                    self.index(),
+                ))
+            }
             Some(TokenKind::Keyword(Keyword::Export)) => {
                 match second.map(|second| *second.kind()) {
                     Some(TokenKind::Keyword(Keyword::Fn)) => {
@@ -244,6 +267,76 @@ impl<'source> Parser<'source> {
         })
     }
 
+    fn eat_import_stmt(&mut self) -> ParseResult<'source, ImportStmt> {
+        let start = self.start_node();
+        self.eat_keyword(Keyword::Import)?;
+        let variant = if self.peek_kind() == TokenKind::Symbol(Symbol::Mul) {
+            self.eat_symbol(Symbol::Mul)?;
+            self.eat_contextual_keyword("as")?;
+            ImportStmtVariant::Namespace(self.eat_ident()?.into())
+        } else {
+            ImportStmtVariant::Named(self.eat_import_specifiers()?)
+        };
+        self.eat_contextual_keyword("from")?;
+        let path = self.eat_string_expr()?.into();
+        self.eat_symbol(Symbol::Semicolon)?;
+
+        Ok(ImportStmt {
+            info: self.end_node(start),
+            variant,
+            path,
+        })
+    }
+
+    fn eat_export_from_stmt(&mut self) -> ParseResult<'source, ExportFromStmt> {
+        let start = self.start_node();
+        self.eat_keyword(Keyword::Export)?;
+        let specifiers = self.eat_import_specifiers()?;
+        self.eat_contextual_keyword("from")?;
+        let path = self.eat_string_expr()?.into();
+        self.eat_symbol(Symbol::Semicolon)?;
+
+        Ok(ExportFromStmt {
+            info: self.end_node(start),
+            specifiers,
+            path,
+        })
+    }
+
+    fn eat_import_specifiers(&mut self) -> ParseResult<'source, Vec<ImportSpecifier>> {
+        let mut specifiers = Vec::new();
+        self.eat_symbol(Symbol::OpenBrace)?;
+
+        while self.peek_kind() != TokenKind::Symbol(Symbol::CloseBrace) {
+            specifiers.push(self.eat_import_specifier()?);
+            if self.peek_kind() == TokenKind::Symbol(Symbol::CloseBrace) {
+                break;
+            }
+
+            self.eat_symbol(Symbol::Comma)?;
+        }
+
+        self.eat_symbol(Symbol::CloseBrace)?;
+        Ok(specifiers)
+    }
+
+    fn eat_import_specifier(&mut self) -> ParseResult<'source, ImportSpecifier> {
+        let start = self.start_node();
+        let name = self.eat_ident()?.into();
+        let alias = if self.peek_contextual_keyword("as") {
+            self.eat_contextual_keyword("as")?;
+            Some(self.eat_ident()?.into())
+        } else {
+            None
+        };
+
+        Ok(ImportSpecifier {
+            info: self.end_node(start),
+            name,
+            alias,
+        })
+    }
+
     fn eat_variable_declaration_stmt(&mut self) -> ParseResult<VariableDeclarationStmt> {
         let start = self.start_node();
         let is_exported = self
@@ -268,6 +361,7 @@ impl<'source> Parser<'source> {
 mod tests {
     use crate::ast::*;
 
+    use super::super::shape;
     use super::Parser;
 
     #[test]
@@ -354,6 +448,33 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn import_stmt() {
+        assert!(matches!(
+            Parser::new("import { a, b as c } from \"./module.regis\";").eat_module_stmt(),
+            Ok(Stmt::Import(..))
+        ));
+        assert!(matches!(
+            Parser::new("import { a, } from \"./module.regis\";").eat_module_stmt(),
+            Ok(Stmt::Import(..))
+        ));
+        assert!(matches!(
+            Parser::new("import * as module from \"./module.regis\";").eat_module_stmt(),
+            Ok(Stmt::Import(..))
+        ));
+        assert!(Parser::new("import { a } from \"./module.regis\";")
+            .eat_stmt()
+            .is_err());
+    }
+
+    #[test]
+    fn export_from_stmt() {
+        assert!(matches!(
+            Parser::new("export { a, b as c } from \"./module.regis\";").eat_module_stmt(),
+            Ok(Stmt::ExportFrom(..))
+        ));
+    }
+
     #[test]
     fn variable_declaration_stmt() {
         assert!(matches!(
@@ -361,4 +482,57 @@ mod tests {
             Ok(Stmt::VariableDeclaration(..))
         ));
     }
+
+    fn assert_round_trip(source: &str) {
+        let chunk = Parser::new(source)
+            .parse()
+            .unwrap_or_else(|error| panic!("{}", error));
+        let unparsed = chunk.to_string();
+        let reparsed = Parser::new(&unparsed)
+            .parse()
+            .unwrap_or_else(|error| panic!("{}: {}", unparsed, error));
+
+        assert_eq!(
+            shape(Node::Chunk(&chunk)),
+            shape(Node::Chunk(&reparsed)),
+            "{}",
+            unparsed
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        assert_round_trip("import { a, b as c } from \"./module.regis\"; import {} from \"./x\";");
+        assert_round_trip("import * as module from \"./module.regis\";");
+        assert_round_trip("export { a, b as c, } from \"./module.regis\";");
+        assert_round_trip("let a = 1; export let b = [a];");
+        assert_round_trip("a = 1; a += 2; a -= 3; a *= 4; a /= 5; a ??= 6;");
+        assert_round_trip("a[0] = 1; a.b.c ??= 2; a[0].b *= 3;");
+        assert_round_trip(
+            "fn run() {} fn () {}; fn f(x) => x; export fn h(a) {} export fn g() => 1",
+        );
+        assert_round_trip("if a {} else if b { return; } else { return 1; }");
+        assert_round_trip("while i < 10 { i += 1; if i == 5 { continue; } } loop { break; }");
+        assert_round_trip("run(); fn {}(); {}.a; (fn => 1)();");
+        assert_round_trip("-1; a - -1; - -1; (-1).b; (-1.5)[0];");
+        assert_round_trip(
+            "fn outer(a) {
+                fn inner(b) {
+                    if b { return [a, b]; }
+                }
+                return inner;
+            }",
+        );
+    }
+
+    #[test]
+    fn unparse_indentation() {
+        let chunk = Parser::new("fn f(x) { if x { return 1; } } f(2);")
+            .parse()
+            .unwrap_or_else(|error| panic!("{}", error));
+        assert_eq!(
+            chunk.to_string(),
+            "fn f(x) {\n    if x {\n        return 1;\n    }\n}\nf(2);\n"
+        );
+    }
 }
@@ -1,12 +1,46 @@
-use std::cell::{Ref, RefCell, RefMut};
 use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::hash::{Hash, Hasher};
 use std::ops::Deref;
-use std::rc::Rc;
+#[cfg(feature = "sync")]
+use std::ops::DerefMut;
+
+#[cfg(not(feature = "sync"))]
+use std::cell::RefCell as Lock;
+#[cfg(not(feature = "sync"))]
+pub use std::cell::{Ref, Ref as MappedRef, RefMut, RefMut as MappedRefMut};
+#[cfg(not(feature = "sync"))]
+use std::rc::{Rc as Pointer, Weak};
+
+#[cfg(feature = "sync")]
+use parking_lot::{
+    MappedRwLockReadGuard, MappedRwLockWriteGuard, RwLock as Lock, RwLockReadGuard,
+    RwLockWriteGuard,
+};
+#[cfg(feature = "sync")]
+use std::cell::RefCell;
+#[cfg(feature = "sync")]
+use std::sync::{Arc as Pointer, Weak};
+
+#[cfg(feature = "sync")]
+pub type MappedRef<'a, T> = Ref<'a, T>;
+#[cfg(feature = "sync")]
+pub type MappedRefMut<'a, T> = RefMut<'a, T>;
+
+// Bound for anything stored in shared values or the interpreter, so that everything can be sent
+// between threads when the "sync" feature is enabled.
+#[cfg(not(feature = "sync"))]
+pub trait MaybeSend {}
+#[cfg(not(feature = "sync"))]
+impl<T: ?Sized> MaybeSend for T {}
+
+#[cfg(feature = "sync")]
+pub trait MaybeSend: Send + Sync {}
+#[cfg(feature = "sync")]
+impl<T: ?Sized + Send + Sync> MaybeSend for T {}
 
 #[derive(Debug)]
 pub struct SharedMutable<T> {
-    inner: Rc<RefCell<T>>,
+    inner: Pointer<Lock<T>>,
 }
 
 impl<T> Display for SharedMutable<T>
@@ -14,7 +48,7 @@ where
     T: Display,
 {
     fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
-        self.inner.borrow().fmt(formatter)
+        self.borrow().fmt(formatter)
     }
 }
 
@@ -27,7 +61,7 @@ impl<T> From<T> for SharedMutable<T> {
 impl<T> Clone for SharedMutable<T> {
     fn clone(&self) -> Self {
         Self {
-            inner: Rc::clone(&self.inner),
+            inner: Pointer::clone(&self.inner),
         }
     }
 }
@@ -37,7 +71,7 @@ where
     T: Hash,
 {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        self.inner.borrow().hash(state)
+        self.borrow().hash(state)
     }
 }
 
@@ -46,7 +80,7 @@ where
     T: PartialEq,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.inner.eq(&other.inner)
+        Pointer::ptr_eq(&self.inner, &other.inner) || *self.borrow() == *other.borrow()
     }
 }
 
@@ -55,22 +89,219 @@ impl<T> Eq for SharedMutable<T> where T: PartialEq {}
 impl<T> SharedMutable<T> {
     pub fn new(value: T) -> Self {
         Self {
-            inner: Rc::new(RefCell::new(value)),
+            inner: Pointer::new(Lock::new(value)),
         }
     }
 
+    #[cfg(not(feature = "sync"))]
     pub fn borrow(&self) -> Ref<'_, T> {
         self.inner.borrow()
     }
 
+    #[cfg(not(feature = "sync"))]
     pub fn borrow_mut(&self) -> RefMut<'_, T> {
         self.inner.borrow_mut()
     }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn borrow_map<U: ?Sized, F>(&self, map: F) -> Option<MappedRef<'_, U>>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        Ref::filter_map(self.borrow(), map).ok()
+    }
+
+    #[cfg(not(feature = "sync"))]
+    pub fn borrow_mut_map<U: ?Sized, F>(&self, map: F) -> Option<MappedRefMut<'_, U>>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        RefMut::filter_map(self.borrow_mut(), map).ok()
+    }
+
+    // Borrows wait for other threads to release the lock. Waiting for a lock the current thread
+    // holds itself would deadlock instead, so that fails like a RefCell does.
+    #[cfg(feature = "sync")]
+    pub fn borrow(&self) -> Ref<'_, T> {
+        let (held, reentrant) = Held::acquire(self.address(), false);
+
+        // A plain read waits behind writers from other threads, which would deadlock if they're
+        // waiting for the read this thread already holds.
+        let guard = if reentrant {
+            self.inner.read_recursive()
+        } else {
+            self.inner.read()
+        };
+
+        Ref {
+            guard: RwLockReadGuard::map(guard, |value| value),
+            _held: held,
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        let (held, _) = Held::acquire(self.address(), true);
+        RefMut {
+            guard: RwLockWriteGuard::map(self.inner.write(), |value| value),
+            _held: held,
+        }
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn borrow_map<U: ?Sized, F>(&self, map: F) -> Option<MappedRef<'_, U>>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        let Ref { guard, _held } = self.borrow();
+        MappedRwLockReadGuard::try_map(guard, map)
+            .ok()
+            .map(|guard| Ref { guard, _held })
+    }
+
+    #[cfg(feature = "sync")]
+    pub fn borrow_mut_map<U: ?Sized, F>(&self, map: F) -> Option<MappedRefMut<'_, U>>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        let RefMut { guard, _held } = self.borrow_mut();
+        MappedRwLockWriteGuard::try_map(guard, map)
+            .ok()
+            .map(|guard| RefMut { guard, _held })
+    }
+
+    pub fn downgrade(&self) -> WeakMutable<T> {
+        WeakMutable {
+            inner: Pointer::downgrade(&self.inner),
+        }
+    }
+
+    pub fn strong_count(&self) -> usize {
+        Pointer::strong_count(&self.inner)
+    }
+
+    pub fn address(&self) -> usize {
+        Pointer::as_ptr(&self.inner) as *const () as usize
+    }
+}
+
+#[cfg(feature = "sync")]
+thread_local! {
+    // The locks held by the current thread, and whether each one is held exclusively.
+    static HELD: RefCell<Vec<(usize, bool)>> = const { RefCell::new(Vec::new()) };
+}
+
+// Records that the current thread holds a lock for as long as it's alive.
+#[cfg(feature = "sync")]
+#[derive(Debug)]
+struct Held {
+    address: usize,
+    exclusive: bool,
+}
+
+#[cfg(feature = "sync")]
+impl Held {
+    // Panics if the borrow conflicts with one the current thread already holds. Otherwise, returns
+    // whether the thread already shares the lock.
+    fn acquire(address: usize, exclusive: bool) -> (Self, bool) {
+        HELD.with(|held| {
+            let mut held = held.borrow_mut();
+            let current = held
+                .iter()
+                .filter(|(other, _)| *other == address)
+                .map(|(_, exclusive)| *exclusive)
+                .max();
+            match (current, exclusive) {
+                (Some(true), false) => panic!("already mutably borrowed"),
+                (Some(_), true) => panic!("already borrowed"),
+                _ => {}
+            }
+
+            held.push((address, exclusive));
+            (Self { address, exclusive }, current.is_some())
+        })
+    }
+}
+
+#[cfg(feature = "sync")]
+impl Drop for Held {
+    fn drop(&mut self) {
+        // The registry may already be gone if a guard is dropped while the thread exits.
+        let _ = HELD.try_with(|held| {
+            let mut held = held.borrow_mut();
+            if let Some(index) = held
+                .iter()
+                .rposition(|entry| *entry == (self.address, self.exclusive))
+            {
+                held.swap_remove(index);
+            }
+        });
+    }
+}
+
+#[cfg(feature = "sync")]
+pub struct Ref<'a, T: ?Sized> {
+    guard: MappedRwLockReadGuard<'a, T>,
+    _held: Held,
+}
+
+#[cfg(feature = "sync")]
+impl<T: ?Sized> Deref for Ref<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+#[cfg(feature = "sync")]
+pub struct RefMut<'a, T: ?Sized> {
+    guard: MappedRwLockWriteGuard<'a, T>,
+    _held: Held,
+}
+
+#[cfg(feature = "sync")]
+impl<T: ?Sized> Deref for RefMut<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+#[cfg(feature = "sync")]
+impl<T: ?Sized> DerefMut for RefMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+#[derive(Debug)]
+pub struct WeakMutable<T> {
+    inner: Weak<Lock<T>>,
+}
+
+impl<T> Clone for WeakMutable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Weak::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> WeakMutable<T> {
+    pub fn upgrade(&self) -> Option<SharedMutable<T>> {
+        self.inner.upgrade().map(|inner| SharedMutable { inner })
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.inner.strong_count() == 0
+    }
 }
 
 #[derive(Debug)]
 pub struct SharedImmutable<T> {
-    inner: Rc<T>,
+    inner: Pointer<T>,
 }
 
 impl<T> Display for SharedImmutable<T>
@@ -105,7 +336,7 @@ impl<T> Deref for SharedImmutable<T> {
 impl<T> Clone for SharedImmutable<T> {
     fn clone(&self) -> Self {
         Self {
-            inner: Rc::clone(&self.inner),
+            inner: Pointer::clone(&self.inner),
         }
     }
 }
@@ -133,13 +364,100 @@ impl<T> Eq for SharedImmutable<T> where T: PartialEq {}
 impl<T> SharedImmutable<T> {
     pub fn new(value: T) -> Self {
         Self {
-            inner: Rc::new(value),
+            inner: Pointer::new(value),
+        }
+    }
+
+    pub fn downgrade(&self) -> WeakImmutable<T> {
+        WeakImmutable {
+            inner: Pointer::downgrade(&self.inner),
+        }
+    }
+
+    pub fn strong_count(&self) -> usize {
+        Pointer::strong_count(&self.inner)
+    }
+
+    pub fn address(&self) -> usize {
+        Pointer::as_ptr(&self.inner) as *const () as usize
+    }
+}
+
+#[derive(Debug)]
+pub struct WeakImmutable<T> {
+    inner: Weak<T>,
+}
+
+impl<T> Clone for WeakImmutable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Weak::clone(&self.inner),
         }
     }
 }
 
+impl<T> WeakImmutable<T> {
+    pub fn upgrade(&self) -> Option<SharedImmutable<T>> {
+        self.inner.upgrade().map(|inner| SharedImmutable { inner })
+    }
+
+    pub fn is_dropped(&self) -> bool {
+        self.inner.strong_count() == 0
+    }
+}
+
 impl<T: Clone> SharedImmutable<T> {
     pub fn clone_inner(&self) -> T {
         (*self.inner).clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "already mutably borrowed")]
+    fn reentrant_borrow() {
+        let shared = SharedMutable::new(0);
+        let _guard = shared.borrow_mut();
+        drop(shared.borrow());
+    }
+
+    #[test]
+    #[should_panic(expected = "already borrowed")]
+    fn reentrant_borrow_mut() {
+        let shared = SharedMutable::new(0);
+        let _guard = shared.borrow();
+        drop(shared.borrow_mut());
+    }
+
+    #[test]
+    fn shared_borrows() {
+        let shared = SharedMutable::new(0);
+        let first = shared.borrow();
+        let second = shared.borrow();
+        assert_eq!(*first + *second, 0);
+    }
+
+    #[cfg(feature = "sync")]
+    #[test]
+    fn borrow_from_other_thread() {
+        use std::thread;
+        use std::time::Duration;
+
+        // Other threads wait for the borrow to end instead of failing.
+        let shared = SharedMutable::new(0);
+        let mut guard = shared.borrow_mut();
+        let thread = {
+            let shared = shared.clone();
+            thread::spawn(move || *shared.borrow_mut() += 1)
+        };
+
+        thread::sleep(Duration::from_millis(50));
+        *guard += 1;
+        drop(guard);
+        thread.join().unwrap();
+        assert_eq!(*shared.borrow(), 2);
+    }
+}
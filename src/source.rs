@@ -3,5 +3,5 @@ mod path;
 mod span;
 
 pub use self::location::Location;
-pub use self::path::{CanonicalPath, RelativePath};
+pub use self::path::{CanonicalPath, ModuleKey, RelativePath};
 pub use self::span::Span;
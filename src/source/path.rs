@@ -1,29 +1,71 @@
 use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 use std::io::Result as IOResult;
 
+use crate::shared::SharedImmutable;
+
+// Where a path comes from. Real paths were canonicalized by the filesystem, virtual paths name
+// modules that only exist in memory and are only normalized lexically.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum PathKind {
+    Real,
+    Virtual,
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct CanonicalPath {
     path: PathBuf,
+    kind: PathKind,
 }
 
 impl CanonicalPath {
     pub fn from<P: AsRef<Path>>(path: &P) -> Option<Self> {
         Some(Self {
             path: fs::canonicalize(path).ok()?,
+            kind: PathKind::Real,
         })
     }
 
+    pub fn from_virtual(path: impl AsRef<Path>) -> Self {
+        // Virtual paths can't be canonicalized by the filesystem, so "." and ".." components are
+        // resolved lexically instead.
+        let mut result = PathBuf::new();
+        for component in path.as_ref().components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    result.pop();
+                }
+                other => result.push(other),
+            }
+        }
+
+        Self {
+            path: result,
+            kind: PathKind::Virtual,
+        }
+    }
+
+    pub fn is_virtual(&self) -> bool {
+        self.kind == PathKind::Virtual
+    }
+
     pub fn join(&self, relative: RelativePath) -> Option<Self> {
-        Self::from(&self.path.join(relative))
+        match self.kind {
+            PathKind::Real => Self::from(&self.path.join(relative)),
+            PathKind::Virtual => Some(Self::from_virtual(self.path.join(relative))),
+        }
     }
 
     pub fn parent(&self) -> Self {
         let mut path = self.path.clone();
         path.pop();
-        Self { path }
+        Self {
+            path,
+            kind: self.kind,
+        }
     }
 
     pub fn read(&self) -> IOResult<String> {
@@ -43,6 +85,21 @@ impl Display for CanonicalPath {
     }
 }
 
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub enum ModuleKey {
+    Path(CanonicalPath),
+    Native(SharedImmutable<String>),
+}
+
+impl Display for ModuleKey {
+    fn fmt(&self, formatter: &mut Formatter<'_>) -> FormatResult {
+        match self {
+            Self::Path(path) => write!(formatter, "{}", path),
+            Self::Native(name) => write!(formatter, "{}", **name),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct RelativePath {
     path: PathBuf,